@@ -1,2 +1,2 @@
 import { main } from "./js/Main.js";
-main;
+main();
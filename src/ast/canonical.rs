@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
 use super::*;
@@ -9,41 +10,53 @@ use crate::ast::Span;
 pub struct Module {
     pub unions: HashMap<Name, Union>,
     pub aliases: HashMap<Name, Alias>,
-    pub external_types: HashMap<Name, String>,
-    pub definitions: Definitions,
+    pub external_types: HashMap<Name, External>,
+    pub constructors: HashMap<Name, Constructor>,
+    pub definitions: Vec<Definitions>, // in dependency order
     pub imports: Vec<ModuleName>,
     pub exports: Vec<Export>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Export {
     Value(Name),
     ClosedType(Name),
     OpenType(Name),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Alias {
     pub variables: Vec<Name>,
     pub other: Type,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct External {
+    pub variables: Vec<Name>,
+    pub internal_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Union {
     pub variables: Vec<Name>,
-    pub constructors: Vec<Name>,
+    pub variants: Vec<Variant>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Variant {
+    pub name: Name,
+    pub args: Vec<Located<Type>>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Definitions {
-    None,
     Recursive(Vec<Definition>),
     NonRecursive(Definition),
 }
 
 #[derive(Debug, Clone)]
 pub struct Definition {
-    pub annotation: Annotation,
+    pub annotation: Option<Annotation>, // definitions without a type signature have theirs inferred
     pub name: Name,
     pub expr: Expr,
 }
@@ -63,23 +76,44 @@ pub enum Expr_ {
     Float(f64),
     String(Name),
     List(Vec<Expr>),
+    Tuple(Vec<Expr>),
     Ap { function: Box<Expr>, arg: Box<Expr> },
     Op { op: Operator, lhs: Box<Expr>, rhs: Box<Expr> },
     Let { name: Name, expr: Box<Expr>, body: Box<Expr> },
     LetRec { defs: Vec<(Name, Expr)>, body: Box<Expr> },
     Lambda { arg: Name, body: Box<Expr> },
     If { cond: Box<Expr>, true_branch: Box<Expr>, false_branch: Box<Expr> },
+    Destruct { pattern: Pattern, expr: Box<Expr>, body: Box<Expr> }, // A `let` or lambda argument with a pattern, which must always match
     When { expr: Box<Expr>, first_alternative: Box<(Pattern, Expr)>, rest_alternatives: Vec<(Pattern, Expr)> },
-    Access { record: Box<Expr>, field: Name }, // TODO: add records
+    Record(HashMap<Name, Expr>),
+    Access { record: Box<Expr>, field: Name },
+    Update { record: Box<Expr>, fields: HashMap<Name, Expr> },
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Constructor {
     pub tag: u16,
     pub arity: u16,
     pub annotation: Annotation,
 }
 
+impl Constructor {
+    // the union a constructor belongs to is whatever type its annotation finally returns.
+    pub fn union(&self) -> &Qualified<Name> {
+        let mut tipe = &self.annotation.tipe;
+        while let Type::Lambda(_, ret) = tipe {
+            tipe = ret;
+        }
+        while let Type::Application(cons, _) = tipe {
+            tipe = cons;
+        }
+        match tipe {
+            Type::Identifier(name) => name,
+            _ => unreachable!("constructors always return a union type"),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Operator {
     Or,
@@ -104,13 +138,13 @@ pub enum Operator {
 
 // TYPES
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Type {
     Variable(Name),
     Identifier(Qualified<Name>),
     Application(Box<Type>, Box<Type>),
     Lambda(Box<Type>, Box<Type>),
-    Record(HashMap<Name, Type>),
+    Record(HashMap<Name, Type>, Option<Name>), // Fields, and the variable standing for any other fields
     Unit,
     Tuple(Box<Type>, Box<Type>, Vec<Type>),
 }
@@ -121,12 +155,12 @@ impl Type {
             Type::Variable(var) => HashSet::from([var.clone()]),
             Type::Identifier(_) => HashSet::new(),
             Type::Unit => HashSet::new(),
-            Type::Record(fields) => fields
-                .iter()
-                .map(|(_, tipe)| tipe.free_variables())
-                .fold(HashSet::new(), |all, one| {
-                    all.union(&one).map(|s| s.clone()).collect()
-                }),
+            Type::Record(fields, extension) => {
+                fields.iter().map(|(_, tipe)| tipe.free_variables()).fold(
+                    extension.iter().cloned().collect(),
+                    |all: HashSet<Name>, one| all.union(&one).map(|s| s.clone()).collect(),
+                )
+            }
             Type::Tuple(a, b, rest) => {
                 let mut vars = vec![];
                 vars.push(a.free_variables());
@@ -157,7 +191,7 @@ pub enum Primative {
     String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Annotation {
     pub quantified: HashSet<Name>,
     pub tipe: Type,
@@ -180,6 +214,6 @@ pub type Pattern = Located<Pattern_>;
 pub enum Pattern_ {
     Wildcard,
     Identifier(Name),
-    Constructor(Constructor, Vec<Pattern>),
+    Constructor(Qualified<Constructor>, Vec<Pattern>),
     Tuple(Vec<Pattern>),
 }
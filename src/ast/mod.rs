@@ -1,4 +1,5 @@
 use nom_locate::LocatedSpan;
+use serde::{Deserialize, Serialize};
 use std::fmt;
 
 pub mod canonical;
@@ -9,7 +10,7 @@ pub type Span<'a> = LocatedSpan<&'a str>;
 
 pub type Name = String;
 
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ModuleName(pub Vec<String>);
 
 impl fmt::Display for ModuleName {
@@ -18,7 +19,7 @@ impl fmt::Display for ModuleName {
     }
 }
 
-#[derive(Debug, Clone)] // TODO: Add top level for further optimizations?
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)] // TODO: Add top level for further optimizations?
 pub enum Qualified<T> {
     Foreign { module: ModuleName, member: T },
     Local(T),
@@ -35,7 +36,7 @@ impl<T> Qualified<T> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Located<T> {
     pub region: Region,
     pub inner: T,
@@ -54,7 +55,7 @@ impl<T> Located<T> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Region {
     pub start: Position,
     pub end: Position,
@@ -74,7 +75,7 @@ impl Region {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
@@ -39,24 +39,47 @@ pub enum Expr {
     Float(f64),
     String(String),
     List(Vec<Expr>),
+    Tuple(Vec<Expr>),
     Ap { function: Box<Expr>, arg: Box<Expr> },
     Op { op: Operator, lhs: Box<Expr>, rhs: Box<Expr> },
     Let { name: Name, expr: Box<Expr>, body: Box<Expr> },
     LetRec { defs: Vec<(Name, Expr)>, body: Box<Expr> },
     Lambda { arg: Name, body: Box<Expr> },
     If { cond: Box<Expr>, true_branch: Box<Expr>, false_branch: Box<Expr> },
-    When { expr: Box<Expr>, decision_tree: Box<DecisionTree<Expr>> },
+    When { expr: Box<Expr>, decision_tree: Box<DecisionTree<usize>>, branches: Vec<Branch> },
     Constructor { tag: u16, arity: u16 },
+    Record(Vec<(Name, Expr)>),
+    Access { record: Box<Expr>, field: Name },
+    Update { record: Box<Expr>, fields: Vec<(Name, Expr)> },
+}
+
+// a branch of a `when` expression, along with where each of its variables live in the value
+// being matched on. The decision tree refers to branches by their index so that no branch is
+// duplicated when several leaves of the tree end up in the same place.
+#[derive(Debug, Clone)]
+pub struct Branch {
+    pub bindings: Vec<(Name, Path)>,
+    pub body: Expr,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Path {
+    Root,
+    Index(u16, Box<Path>),
 }
 
 #[derive(Debug, Clone)]
 pub enum Test {
-    Always,
-    IsConstructor,
+    IsConstructor(u16),
+    IsBool(bool),
 }
 
 #[derive(Debug, Clone)]
 pub enum DecisionTree<T> {
     Succeed(T),
-    If(Test, T, T),
+    Switch {
+        path: Path,
+        edges: Vec<(Test, DecisionTree<T>)>,
+        fallback: Option<Box<DecisionTree<T>>>,
+    },
 }
@@ -5,12 +5,40 @@ use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone)]
 pub struct Module {
-    pub name: ModuleName,
+    pub name: Located<ModuleName>,
     pub exports: Vec<Export>,
-    pub imports: Vec<ModuleName>,
+    pub imports: Vec<Import>,
     pub types: HashMap<Name, TypeDefinition>,
     pub values: HashMap<Name, Expr>,
     pub annotations: HashMap<Name, Type>,
+    pub duplicates: Vec<Duplicate>,
+    // the doc comment written just before a declaration, whether that is a value's signature or
+    // its definition, or a type. Only the first one is kept if there are several for a name.
+    pub docs: HashMap<Name, String>,
+}
+
+// a second declaration of a name, which is otherwise ignored in favour of the first.
+#[derive(Debug, Clone)]
+pub struct Duplicate {
+    pub declaration: Declaration,
+    pub name: Name,
+    pub first: Region,
+    pub second: Region,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Declaration {
+    Value,
+    Signature,
+    Type,
+    Constructor,
+}
+
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub name: Located<ModuleName>,
+    pub alias: Option<Name>, // what the module is called here, if not its own name
+    pub exposing: Vec<Located<Export>>, // members which can be used without qualifying them
 }
 
 #[derive(Debug, Clone)]
@@ -24,20 +52,31 @@ pub enum Export {
 pub enum TypeDefinition {
     Alias(Alias),
     Union(Union),
-    External(String),
+    External(External),
 }
 
 impl TypeDefinition {
-    pub fn uses(&self, name: &Name) -> bool {
+    // whether the definition refers to another type of the module it is in, either unqualified or
+    // qualified with the module's own name.
+    pub fn uses(&self, module: &ModuleName, name: &Name) -> bool {
         match self {
-            TypeDefinition::Alias(alias) => todo!(),
+            TypeDefinition::Alias(alias) => alias.other.inner.uses(module, name),
             TypeDefinition::Union(union) => union
                 .variants
                 .iter()
-                .any(|variant| variant.args.iter().any(|arg| arg.inner.uses(name))),
+                .any(|variant| variant.args.iter().any(|arg| arg.inner.uses(module, name))),
             TypeDefinition::External(_) => false,
         }
     }
+
+    // the type variables a type is defined with, one for each argument it has to be given.
+    pub fn variables(&self) -> &[Name] {
+        match self {
+            TypeDefinition::Alias(alias) => &alias.variables,
+            TypeDefinition::Union(union) => &union.variables,
+            TypeDefinition::External(external) => &external.variables,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +85,12 @@ pub struct Alias {
     pub other: Type,
 }
 
+#[derive(Debug, Clone)]
+pub struct External {
+    pub variables: Vec<Name>,
+    pub internal_name: String,
+}
+
 #[derive(Debug, Clone)]
 pub struct Union {
     pub variables: Vec<Name>,
@@ -54,7 +99,7 @@ pub struct Union {
 
 #[derive(Debug, Clone)]
 pub struct Constructor {
-    pub name: Name,
+    pub name: Located<Name>,
     pub args: Vec<Type>,
 }
 
@@ -65,10 +110,10 @@ pub struct Value {
 }
 
 pub enum Statement {
-    Import(ModuleName),
-    LetType(Name, TypeDefinition),
-    LetSignature(Name, Type),
-    LetValue(Name, Expr),
+    Import(Import),
+    LetType(Located<Name>, TypeDefinition),
+    LetSignature(Located<Name>, Type),
+    LetValue(Located<Name>, Expr),
 }
 
 // TYPE
@@ -83,7 +128,7 @@ pub enum Type_ {
     QualifiedIdentifier(ModuleName, Name),
     Variable(Name),
     Fn(Box<Type>, Box<Type>),
-    Record(HashMap<Name, Type>),
+    Record(HashMap<Name, Type>, Option<Name>), // Fields, and the variable standing for any other fields
     Tuple(Box<Type>, Box<Type>, Vec<Type>),
 }
 
@@ -110,12 +155,13 @@ impl Type_ {
             Type_::Identifier(_) => HashSet::new(),
             Type_::QualifiedIdentifier(_, _) => HashSet::new(),
             Type_::Unit => HashSet::new(),
-            Type_::Record(fields) => fields
+            Type_::Record(fields, extension) => fields
                 .iter()
                 .map(|(_, tipe)| tipe.inner.free_variables())
-                .fold(HashSet::new(), |all, one| {
-                    all.union(&one).map(|s| s.clone()).collect()
-                }),
+                .fold(
+                    extension.iter().cloned().collect(),
+                    |all: HashSet<Name>, one| all.union(&one).map(|s| s.clone()).collect(),
+                ),
             Type_::Tuple(a, b, rest) => {
                 let mut vars = vec![];
                 vars.push(a.inner.free_variables());
@@ -133,23 +179,27 @@ impl Type_ {
         }
     }
 
-    fn uses(&self, name: &Name) -> bool {
+    fn uses(&self, module: &ModuleName, name: &Name) -> bool {
         match self {
             Type_::Identifier(arg_name) => arg_name == name,
             Type_::Constructor(cons, arg, rest) => {
-                cons.inner.uses(name)
-                    || arg.inner.uses(name)
-                    || rest.iter().any(|arg| arg.inner.uses(name))
+                cons.inner.uses(module, name)
+                    || arg.inner.uses(module, name)
+                    || rest.iter().any(|arg| arg.inner.uses(module, name))
             }
             Type_::Unit => false,
-            Type_::QualifiedIdentifier(_, _) => false,
+            Type_::QualifiedIdentifier(qualifier, arg_name) => {
+                qualifier == module && arg_name == name
+            }
             Type_::Variable(_) => false,
-            Type_::Fn(lhs, rhs) => lhs.inner.uses(name) || rhs.inner.uses(name),
-            Type_::Record(fields) => fields.iter().any(|(_, tipe)| tipe.inner.uses(name)),
+            Type_::Fn(lhs, rhs) => lhs.inner.uses(module, name) || rhs.inner.uses(module, name),
+            Type_::Record(fields, _) => {
+                fields.iter().any(|(_, tipe)| tipe.inner.uses(module, name))
+            }
             Type_::Tuple(a, b, rest) => {
-                a.inner.uses(name)
-                    || b.inner.uses(name)
-                    || rest.iter().any(|arg| arg.inner.uses(name))
+                a.inner.uses(module, name)
+                    || b.inner.uses(module, name)
+                    || rest.iter().any(|arg| arg.inner.uses(module, name))
             }
         }
     }
@@ -164,10 +214,31 @@ pub enum Pattern_ {
     Wildcard,
     Identifier(Name),
     Constructor(Name, Vec<Pattern>),
+    QualifiedConstructor(ModuleName, Name, Vec<Pattern>),
+    EmptyList,
     Cons(Box<Pattern>, Box<Pattern>),
     Tuple(Vec<Pattern>),
 }
 
+impl Pattern_ {
+    // the names a pattern brings into scope.
+    pub fn bindings(&self) -> HashSet<Name> {
+        match self {
+            Pattern_::Wildcard | Pattern_::EmptyList => HashSet::new(),
+            Pattern_::Identifier(name) => HashSet::from([name.clone()]),
+            Pattern_::Constructor(_, args)
+            | Pattern_::QualifiedConstructor(_, _, args)
+            | Pattern_::Tuple(args) => args.iter().flat_map(|arg| arg.inner.bindings()).collect(),
+            Pattern_::Cons(element, list) => element
+                .inner
+                .bindings()
+                .union(&list.inner.bindings())
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
 // EXPRESSION
 
 pub type Expr = Located<Expr_>;
@@ -196,6 +267,8 @@ pub enum Expr_ {
     Float(f32),
     String(String),
     Record(HashMap<Name, Expr>),
+    Access(Box<Expr>, Name),
+    Update(Box<Expr>, HashMap<Name, Expr>),
     List(Vec<Expr>),
     Tuple(Vec<Expr>),
 }
@@ -4,49 +4,165 @@ use crate::{
     ast::{
         self,
         canonical::{self, Annotation, Definition, Union},
-        source, Located, ModuleName, Name, Qualified, Span,
+        source, Located, ModuleName, Name, Qualified, Region, Span,
     },
+    graph,
+    interface::Interface,
+    report::error::{alias, import, kind, naming, tipe, Error},
     util,
 };
 
-// resolve variable names, cycle detection
+// resolve variable names
 
-pub fn canonicalize(source_modules: Vec<source::Module>) -> HashMap<ModuleName, canonical::Module> {
-    let mut uncanonicalized_dependencies: HashMap<ModuleName, HashSet<ModuleName>> = source_modules
+// modules are canonicalized after everything they import, which is only possible when the imports
+// don't form a cycle.
+pub fn check_imports(
+    modules: &[source::Module],
+    interfaces: &HashMap<ModuleName, Interface>,
+) -> Result<(), Vec<import::Error>> {
+    let names = modules
+        .iter()
+        .map(|module| &module.name.inner)
+        .chain(interfaces.keys())
+        .map(|name| name.to_string())
+        .collect::<Vec<String>>();
+    let missing = modules
+        .iter()
+        .flat_map(|module| {
+            module
+                .imports
+                .iter()
+                .filter(|import| {
+                    !interfaces.contains_key(&import.name.inner)
+                        && modules
+                            .iter()
+                            .all(|other| other.name.inner != import.name.inner)
+                })
+                .map(|import| import::Error::Missing {
+                    module: module.name.inner.clone(),
+                    region: import.name.region.clone(),
+                    import: import.name.inner.clone(),
+                    suggestions: util::suggestions(
+                        &import.name.inner.to_string(),
+                        names.iter().map(|name| name.as_str()),
+                    ),
+                })
+        })
+        .collect::<Vec<import::Error>>();
+    if !missing.is_empty() {
+        return Err(missing);
+    }
+
+    let graph = import_graph(modules);
+    for component in graph::strongly_connected_components(&graph) {
+        if !graph::is_cyclic(&graph, &component) {
+            continue;
+        }
+
+        let start = component
+            .iter()
+            .min_by_key(|name| name.to_string())
+            .expect("components are never empty");
+        let cycle = graph::cycle(&graph, start).expect("cyclic components contain a cycle");
+        return Err(vec![import::Error::Cycle(
+            cycle
+                .windows(2)
+                .map(|pair| {
+                    let module = modules
+                        .iter()
+                        .find(|module| module.name.inner == pair[0])
+                        .unwrap();
+                    let import = module
+                        .imports
+                        .iter()
+                        .find(|import| import.name.inner == pair[1])
+                        .unwrap();
+                    (pair[0].clone(), import.name.region.clone())
+                })
+                .collect(),
+        )]);
+    }
+    Ok(())
+}
+
+fn import_graph(modules: &[source::Module]) -> Vec<(ModuleName, Vec<ModuleName>)> {
+    modules
         .iter()
         .map(|module| {
             (
-                module.name.clone(),
+                module.name.inner.clone(),
                 module
                     .imports
-                    .clone()
-                    .into_iter()
-                    .collect::<HashSet<ModuleName>>(),
+                    .iter()
+                    .map(|import| import.name.inner.clone())
+                    .collect(),
             )
         })
-        .collect::<HashMap<ModuleName, HashSet<ModuleName>>>();
-
-    let mut env = Environment::new();
+        .collect()
+}
 
-    // TODO: do the cycle detection before the loop, since a cycle would result in a hanging loop
-    while !uncanonicalized_dependencies.is_empty() {
-        let target_module: ModuleName = uncanonicalized_dependencies
+// modules which haven't changed since they were last compiled aren't canonicalized again, their
+// interfaces are used in their place.
+pub fn canonicalize(
+    source_modules: Vec<source::Module>,
+    interfaces: &HashMap<ModuleName, Interface>,
+) -> Result<HashMap<ModuleName, canonical::Module>, Vec<(ModuleName, Error)>> {
+    let mut env = Environment::new(
+        source_modules
             .iter()
-            .filter_map(|(name, deps)| if deps.is_empty() { Some(name) } else { None })
-            .next()
-            .unwrap()
-            .clone();
+            .map(|module| module.name.inner.clone())
+            .chain(interfaces.keys().cloned())
+            .collect(),
+    );
+    for (module_name, interface) in interfaces.iter() {
+        let module = interface.module();
+        let mut types = HashMap::new();
+        for (name, union) in module.unions.iter() {
+            types.insert(name.clone(), TypeDefinition::Union(union.clone()));
+        }
+        for (name, alias) in module.aliases.iter() {
+            types.insert(name.clone(), TypeDefinition::Alias(alias.clone()));
+        }
+        for (name, external) in module.external_types.iter() {
+            types.insert(name.clone(), TypeDefinition::External(external.clone()));
+        }
+        env.qualified_types.insert(module_name.clone(), types);
+        env.qualified_constructors
+            .insert(module_name.clone(), module.constructors.clone());
+        env.qualified_variables.insert(
+            module_name.clone(),
+            interface.values.keys().cloned().collect(),
+        );
+        env.modules.insert(module_name.clone(), module);
+    }
+    let mut errors = vec![];
 
+    // `check_imports` has already ruled out cycles, so every component is a single module.
+    let graph = import_graph(&source_modules);
+    for target_module in graph::strongly_connected_components(&graph)
+        .into_iter()
+        .flatten()
+    {
         let source_module = source_modules
             .iter()
-            .find(|m| m.name == target_module)
+            .find(|m| m.name.inner == target_module)
             .unwrap();
+        env.module = source_module.name.inner.clone();
+        env.variables = source_module.values.keys().cloned().collect();
+        env.imports(&source_module.imports);
+
+        env.local_types = source_module.types.clone();
+        env.errors.extend(
+            recursive_aliases(&source_module.name.inner, &source_module.types)
+                .into_iter()
+                .map(Error::Alias),
+        );
 
         for (name, tipe) in source_module.types.iter() {
-            let push_type = |env: &mut Environment| match tipe {
+            match tipe {
                 source::TypeDefinition::Alias(alias) => {
-                    env.types
-                        .insert(name.clone(), TypeDefinition::Alias(env.alias(alias)));
+                    let alias = env.alias(name, alias);
+                    env.types.insert(name.clone(), TypeDefinition::Alias(alias));
                 }
                 source::TypeDefinition::Union(union) => {
                     let (union, constructors) = env.union_artifacts(name.clone(), union);
@@ -55,37 +171,86 @@ pub fn canonicalize(source_modules: Vec<source::Module>) -> HashMap<ModuleName,
                         env.constructors.insert(name.clone(), constructor);
                     }
                 }
-                source::TypeDefinition::External(name) => {
-                    env.types
-                        .insert(name.clone(), TypeDefinition::External(name.clone()));
+                source::TypeDefinition::External(external) => {
+                    env.types.insert(
+                        name.clone(),
+                        TypeDefinition::External(canonical::External {
+                            variables: external.variables.clone(),
+                            internal_name: external.internal_name.clone(),
+                        }),
+                    );
                 }
             };
-
-            if tipe.uses(name)
-            /* is recursive */
-            {
-                env.recursive_types.insert(name.clone());
-                push_type(&mut env);
-                env.recursive_types.remove(name);
-            } else {
-                push_type(&mut env);
-            }
         }
 
-        // TODO: cycle detection to split SCC and non-recursive definitions
-        let mut defs = vec![];
-        for (name, tipe) in source_module.annotations.iter() {
-            let expr = source_module.values.get(name).unwrap().clone();
-            defs.push(canonical::Definition {
-                annotation: canonical::Annotation {
-                    quantified: tipe.inner.free_variables(),
-                    tipe: env.tipe(tipe),
+        let mut names = source_module.values.keys().collect::<Vec<&Name>>();
+        names.sort();
+        let mut defs = HashMap::new();
+        let mut dependencies = vec![];
+        for name in names {
+            let expr = source_module.values[name].clone();
+            let expr = env.expression(expr);
+            let mut references = env.references.drain().collect::<Vec<Name>>();
+            references.sort();
+            dependencies.push((name.clone(), references));
+            let annotation =
+                source_module
+                    .annotations
+                    .get(name)
+                    .map(|tipe| canonical::Annotation {
+                        quantified: tipe.inner.free_variables(),
+                        tipe: env.tipe(tipe),
+                    });
+            defs.insert(
+                name.clone(),
+                canonical::Definition {
+                    annotation,
+                    name: name.clone(),
+                    expr,
                 },
-                name: name.clone(),
-                expr: env.expression(expr),
-            });
+            );
         }
 
+        let mut signatures = source_module.annotations.iter().collect::<Vec<_>>();
+        signatures.sort_by_key(|(name, _)| *name);
+        for (name, tipe) in signatures {
+            if source_module.values.contains_key(name) {
+                continue;
+            }
+            let suggestions = util::suggestions(
+                name,
+                source_module
+                    .values
+                    .keys()
+                    .filter(|value| !source_module.annotations.contains_key(*value))
+                    .map(|value| value.as_str()),
+            );
+            env.errors
+                .push(Error::Naming(naming::Error::MissingDefinition {
+                    region: tipe.region.clone(),
+                    name: name.clone(),
+                    suggestions,
+                }));
+        }
+
+        // definitions only get grouped together when they refer to each other, so that the rest
+        // can be checked and emitted one at a time after whatever they use.
+        let definitions = graph::strongly_connected_components(&dependencies)
+            .into_iter()
+            .map(|component| {
+                if graph::is_cyclic(&dependencies, &component) {
+                    canonical::Definitions::Recursive(
+                        component
+                            .iter()
+                            .map(|name| defs.remove(name).unwrap())
+                            .collect(),
+                    )
+                } else {
+                    canonical::Definitions::NonRecursive(defs.remove(&component[0]).unwrap())
+                }
+            })
+            .collect();
+
         let mut unions = HashMap::new();
         let mut aliases = HashMap::new();
         let mut external_types = HashMap::new();
@@ -97,8 +262,8 @@ pub fn canonicalize(source_modules: Vec<source::Module>) -> HashMap<ModuleName,
                 TypeDefinition::Union(union) => {
                     unions.insert(name, union);
                 }
-                TypeDefinition::External(internal_name) => {
-                    external_types.insert(name, internal_name);
+                TypeDefinition::External(external) => {
+                    external_types.insert(name, external);
                 }
             };
         }
@@ -107,8 +272,13 @@ pub fn canonicalize(source_modules: Vec<source::Module>) -> HashMap<ModuleName,
             unions,
             aliases,
             external_types,
-            definitions: canonical::Definitions::Recursive(defs),
-            imports: source_module.imports.clone(),
+            constructors: env.constructors.clone(),
+            definitions,
+            imports: source_module
+                .imports
+                .iter()
+                .map(|import| import.name.inner.clone())
+                .collect(),
             exports: source_module
                 .exports
                 .iter()
@@ -119,9 +289,14 @@ pub fn canonicalize(source_modules: Vec<source::Module>) -> HashMap<ModuleName,
                 })
                 .collect(),
         };
-        let module_name = source_module.name.clone();
+        let module_name = source_module.name.inner.clone();
 
         env.modules.insert(module_name.clone(), module);
+        errors.extend(
+            env.errors
+                .drain(..)
+                .map(|error| (module_name.clone(), error)),
+        );
 
         // RESET ENVIRONMENT FOR NEXT MODULE
         env.qualified_types.insert(
@@ -136,22 +311,91 @@ pub fn canonicalize(source_modules: Vec<source::Module>) -> HashMap<ModuleName,
             module_name.clone(),
             std::mem::replace(&mut env.variables, HashSet::new()),
         );
+    }
 
-        uncanonicalized_dependencies.remove(&target_module);
-        uncanonicalized_dependencies
-            .iter_mut()
-            .for_each(|(name, mut deps)| {
-                deps.remove(&target_module);
-            })
+    if errors.is_empty() {
+        Ok(env.modules)
+    } else {
+        Err(errors)
     }
+}
+
+// aliases which refer to themselves, either directly or through other aliases, could never be
+// expanded. Only the definition of a union stops the expansion, so `let Tree = | Node (List Tree);`
+// is fine, while `let Loop = List Loop;` still has to expand `Loop` inside of the application.
+fn recursive_aliases(
+    module: &ModuleName,
+    types: &HashMap<Name, source::TypeDefinition>,
+) -> Vec<alias::Error> {
+    let mut aliases = types
+        .iter()
+        .filter(|(_, tipe)| matches!(tipe, source::TypeDefinition::Alias(_)))
+        .map(|(name, _)| name)
+        .collect::<Vec<&Name>>();
+    aliases.sort();
+    let graph = aliases
+        .iter()
+        .map(|name| {
+            let uses = aliases
+                .iter()
+                .filter(|other| types[*name].uses(module, other))
+                .map(|other| (*other).clone())
+                .collect();
+            ((*name).clone(), uses)
+        })
+        .collect::<Vec<(Name, Vec<Name>)>>();
 
-    env.modules
+    graph::strongly_connected_components(&graph)
+        .into_iter()
+        .filter(|component| graph::is_cyclic(&graph, component))
+        .map(|component| {
+            let start = component.iter().min().expect("components are never empty");
+            let mut cycle = graph::cycle(&graph, start).expect("cyclic components contain a cycle");
+            cycle.pop();
+            alias::Error::Recursive(
+                cycle
+                    .into_iter()
+                    .map(|name| {
+                        let region = match &types[&name] {
+                            source::TypeDefinition::Alias(alias) => alias.other.region.clone(),
+                            _ => unreachable!("only aliases are in the graph"),
+                        };
+                        (name, region)
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
 }
 
 #[derive(Debug)]
 struct Environment {
+    module: ModuleName,
+    module_names: Vec<ModuleName>,
+    errors: Vec<Error>,
+    // the names bound by the lambdas, lets and patterns enclosing the expression being
+    // canonicalized, innermost last.
+    scopes: Vec<HashSet<Name>>,
+    // the local names which have been looked up, along with how deep the scope binding them is,
+    // so that a local `let` can tell whether it refers to itself.
+    local_references: HashSet<(usize, Name)>,
+    // the modules the current module imports, which are the only ones its qualified names can
+    // refer to.
+    imported: HashSet<ModuleName>,
+    // what the current module's imports rename modules to, and the members they bring into scope
+    // without needing to be qualified.
+    module_aliases: HashMap<ModuleName, ModuleName>,
+    // each name lists every import exposing it, so that using one which several of them expose
+    // can be reported as ambiguous.
+    exposed_variables: HashMap<Name, Vec<ModuleName>>,
+    exposed_types: HashMap<Name, Vec<ModuleName>>,
+    exposed_constructors: HashMap<Name, Vec<ModuleName>>,
+    // the top level definitions of the current module referred to since this was last cleared.
+    references: HashSet<Name>,
     modules: HashMap<ModuleName, canonical::Module>,
-    recursive_types: HashSet<Name>,
+    // every type the current module defines, so that types can refer to ones defined after them
+    // or to themselves.
+    local_types: HashMap<Name, source::TypeDefinition>,
     types: HashMap<Name, TypeDefinition>,
     constructors: HashMap<Name, canonical::Constructor>,
     variables: HashSet<Name>,
@@ -164,14 +408,98 @@ struct Environment {
 enum TypeDefinition {
     Alias(canonical::Alias),
     Union(canonical::Union),
-    External(Name),
+    External(canonical::External),
+}
+
+impl TypeDefinition {
+    fn variables(&self) -> &[Name] {
+        match self {
+            TypeDefinition::Alias(alias) => &alias.variables,
+            TypeDefinition::Union(union) => &union.variables,
+            TypeDefinition::External(external) => &external.variables,
+        }
+    }
+}
+
+fn expose(exposed: &mut HashMap<Name, Vec<ModuleName>>, name: &Name, module: &ModuleName) {
+    let modules = exposed.entry(name.clone()).or_default();
+    if !modules.contains(module) {
+        modules.push(module.clone());
+    }
+}
+
+// stands in for a constructor which couldn't be found, once the error has been recorded.
+fn unresolved_constructor() -> canonical::Constructor {
+    canonical::Constructor {
+        tag: 0,
+        arity: 0,
+        annotation: canonical::Annotation {
+            quantified: HashSet::new(),
+            tipe: canonical::Type::Unit,
+        },
+    }
+}
+
+// the `Empty` and `Cons` constructors of `List.List`.
+fn list_constructors() -> (canonical::Constructor, canonical::Constructor) {
+    let type_str = "a".to_owned();
+    let type_var = Box::new(canonical::Type::Variable(type_str.clone()));
+    let list_type = canonical::Type::Application(
+        Box::new(canonical::Type::Identifier(Qualified::Foreign {
+            module: ModuleName(vec!["List".to_owned()]),
+            member: "List".to_owned(),
+        })),
+        type_var.clone(),
+    );
+    let empty = canonical::Constructor {
+        tag: 0,
+        arity: 0,
+        annotation: canonical::Annotation {
+            quantified: HashSet::from([type_str.clone()]),
+            tipe: list_type.clone(),
+        },
+    };
+    let cons = canonical::Constructor {
+        tag: 1,
+        arity: 2,
+        annotation: canonical::Annotation {
+            quantified: HashSet::from([type_str]),
+            tipe: canonical::Type::Lambda(
+                type_var,
+                Box::new(canonical::Type::Lambda(
+                    Box::new(list_type.clone()),
+                    Box::new(list_type),
+                )),
+            ),
+        },
+    };
+    (empty, cons)
+}
+
+// the kinds of things a qualified name can refer to, each of which is looked up separately.
+#[derive(Debug, Clone, Copy)]
+enum Namespace {
+    Value,
+    Type,
+    Constructor,
 }
 
 impl Environment {
-    fn new() -> Self {
+    fn new(module_names: Vec<ModuleName>) -> Self {
         Self {
+            module: ModuleName(vec![]),
+            module_names,
+            errors: vec![],
+            scopes: vec![],
+            local_references: HashSet::new(),
+            imported: HashSet::new(),
+            module_aliases: HashMap::new(),
+            exposed_variables: HashMap::new(),
+            exposed_types: HashMap::new(),
+            exposed_constructors: HashMap::new(),
+            references: HashSet::new(),
             modules: HashMap::new(),
-            recursive_types: HashSet::new(),
+            local_types: HashMap::new(),
             types: HashMap::new(),
             constructors: HashMap::new(),
             variables: HashSet::new(),
@@ -181,51 +509,209 @@ impl Environment {
         }
     }
 
-    fn qualify_type(&self, name: Name) -> crate::ast::Qualified<Name> {
-        if self.recursive_types.contains(&name) || self.types.contains_key(&name) {
+    fn imports(&mut self, imports: &[source::Import]) {
+        self.imported = imports
+            .iter()
+            .map(|import| import.name.inner.clone())
+            .collect();
+        self.module_aliases.clear();
+        self.exposed_variables.clear();
+        self.exposed_types.clear();
+        self.exposed_constructors.clear();
+
+        for import in imports.iter() {
+            let module_name = import.name.inner.clone();
+            if let Some(alias) = &import.alias {
+                self.module_aliases
+                    .insert(ModuleName(vec![alias.clone()]), module_name.clone());
+            }
+
+            for exposed in import.exposing.iter() {
+                let (name, namespace) = match &exposed.inner {
+                    source::Export::Value(name) => (name, Namespace::Value),
+                    source::Export::ClosedType(name) | source::Export::OpenType(name) => {
+                        (name, Namespace::Type)
+                    }
+                };
+                if !self.foreign(&exposed.region, &module_name, name, namespace) {
+                    continue;
+                }
+
+                match &exposed.inner {
+                    source::Export::Value(name) => {
+                        expose(&mut self.exposed_variables, name, &module_name);
+                    }
+                    source::Export::ClosedType(name) => {
+                        expose(&mut self.exposed_types, name, &module_name);
+                    }
+                    // exposing a type with `(..)` exposes its constructors as well, as long as the
+                    // module lets them be seen at all.
+                    source::Export::OpenType(name) => {
+                        expose(&mut self.exposed_types, name, &module_name);
+                        let open = self.modules[&module_name].exports.iter().any(
+                            |export| matches!(export, canonical::Export::OpenType(tipe) if tipe == name),
+                        );
+                        if !open {
+                            self.errors.push(Error::Naming(naming::Error::ClosedType {
+                                region: exposed.region.clone(),
+                                module: module_name.clone(),
+                                name: name.clone(),
+                            }));
+                            continue;
+                        }
+                        let variants = self.modules[&module_name]
+                            .unions
+                            .get(name)
+                            .map(|union| union.variants.iter())
+                            .into_iter()
+                            .flatten();
+                        for variant in variants {
+                            expose(&mut self.exposed_constructors, &variant.name, &module_name);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // the module a qualified name refers to, looking through any alias it was imported under.
+    fn module_name(&self, module_name: ModuleName) -> ModuleName {
+        self.module_aliases
+            .get(&module_name)
+            .cloned()
+            .unwrap_or(module_name)
+    }
+
+    fn qualify_type(&mut self, region: &Region, name: Name) -> Qualified<Name> {
+        if self.local_types.contains_key(&name) {
             return Qualified::Local(name);
         }
 
-        for (module_name, types) in self.qualified_types.iter() {
-            if types.contains_key(&name) {
-                return crate::ast::Qualified::Foreign {
+        match self.exposed_types.get(&name).map(Vec::as_slice) {
+            Some([module_name]) => {
+                return Qualified::Foreign {
                     module: module_name.clone(),
                     member: name,
+                }
+            }
+            Some(modules) => {
+                let modules = modules.to_vec();
+                self.ambiguous(region, &name, modules);
+                return Qualified::Local(name);
+            }
+            None => {}
+        }
+
+        match self.imported_member(region, &name, Namespace::Type) {
+            Some(module) => Qualified::Foreign {
+                module,
+                member: name,
+            },
+            // `imported_member` has reported why, so the name is just kept as it was written.
+            None => Qualified::Local(name),
+        }
+    }
+
+    // an unqualified type or constructor which the current module doesn't define or expose can
+    // still come from an imported module, as long as exactly one of them exports it. Records an
+    // error when none or several do.
+    fn imported_member(
+        &mut self,
+        region: &Region,
+        name: &Name,
+        namespace: Namespace,
+    ) -> Option<ModuleName> {
+        let mut imported = self.imported.iter().cloned().collect::<Vec<ModuleName>>();
+        imported.sort_by_key(|module| module.to_string());
+        let (exporting, private): (Vec<ModuleName>, Vec<ModuleName>) = imported
+            .into_iter()
+            .filter(|module| self.members(module, namespace).contains(&name))
+            .partition(|module| self.exported(module, name, namespace));
+
+        match (exporting.as_slice(), private.first()) {
+            ([module], _) => return Some(module.clone()),
+            ([], Some(module)) => {
+                let module = module.clone();
+                self.not_exported(region, &module, name, namespace);
+            }
+            ([], None) => {
+                let (local, exposed) = match namespace {
+                    Namespace::Type => (
+                        self.local_types.keys().collect::<Vec<&Name>>(),
+                        self.exposed_types.keys().collect::<Vec<&Name>>(),
+                    ),
+                    _ => (
+                        self.constructors.keys().collect(),
+                        self.exposed_constructors.keys().collect(),
+                    ),
                 };
+                let suggestions = util::suggestions(
+                    name,
+                    local
+                        .into_iter()
+                        .chain(exposed)
+                        .chain(self.imported.iter().flat_map(|module| {
+                            self.members(module, namespace)
+                                .into_iter()
+                                .filter(|member| self.exported(module, member, namespace))
+                        }))
+                        .map(|name| name.as_str()),
+                );
+                self.errors.push(Error::Naming(match namespace {
+                    Namespace::Type => naming::Error::UnknownType {
+                        region: region.clone(),
+                        name: name.clone(),
+                        suggestions,
+                    },
+                    _ => naming::Error::UnknownConstructor {
+                        region: region.clone(),
+                        name: name.clone(),
+                        suggestions,
+                    },
+                }));
             }
+            (modules, _) => self.ambiguous(region, name, modules.to_vec()),
         }
+        None
+    }
 
-        return todo!("Could not qualify {}", name);
+    fn ambiguous(&mut self, region: &Region, name: &Name, mut modules: Vec<ModuleName>) {
+        modules.sort_by_key(|module| module.to_string());
+        self.errors.push(Error::Naming(naming::Error::Ambiguous {
+            region: region.clone(),
+            name: name.clone(),
+            modules,
+        }));
     }
 
-    fn tipe(&self, tipe: &source::Type) -> canonical::Type {
+    fn tipe(&mut self, tipe: &source::Type) -> canonical::Type {
         match &tipe.inner {
             source::Type_::Unit => canonical::Type::Unit,
-            source::Type_::Constructor(cons, first_type, types) => types.iter().fold(
-                canonical::Type::Application(
-                    Box::new(self.tipe(cons)),
-                    Box::new(self.tipe(first_type)),
-                ),
-                |cons, arg| canonical::Type::Application(Box::new(cons), Box::new(self.tipe(arg))),
-            ),
-            source::Type_::Variable(name) => canonical::Type::Variable(name.clone()),
-            source::Type_::Identifier(name) => {
-                canonical::Type::Identifier(self.qualify_type(name.to_owned()))
+            source::Type_::Constructor(cons, first_type, types) => {
+                let cons = self.type_constructor(cons);
+                self.type_arguments(&tipe.region, &cons, types.len() + 1);
+                types.iter().fold(
+                    canonical::Type::Application(Box::new(cons), Box::new(self.tipe(first_type))),
+                    |cons, arg| {
+                        canonical::Type::Application(Box::new(cons), Box::new(self.tipe(arg)))
+                    },
+                )
             }
-            source::Type_::QualifiedIdentifier(module_name, name) => {
-                canonical::Type::Identifier(Qualified::Foreign {
-                    module: module_name.clone(),
-                    member: name.clone(),
-                })
+            source::Type_::Variable(name) => canonical::Type::Variable(name.clone()),
+            source::Type_::Identifier(_) | source::Type_::QualifiedIdentifier(_, _) => {
+                let cons = self.type_constructor(tipe);
+                self.type_arguments(&tipe.region, &cons, 0);
+                cons
             }
             source::Type_::Fn(f, x) => {
                 canonical::Type::Lambda(Box::new(self.tipe(f)), Box::new(self.tipe(x)))
             }
-            source::Type_::Record(fields) => canonical::Type::Record(
+            source::Type_::Record(fields, extension) => canonical::Type::Record(
                 fields
                     .iter()
                     .map(|(field, tipe)| (field.clone(), self.tipe(tipe)))
                     .collect(),
+                extension.clone(),
             ),
             source::Type_::Tuple(first, second, rest) => canonical::Type::Tuple(
                 Box::new(self.tipe(first)),
@@ -235,7 +721,71 @@ impl Environment {
         }
     }
 
-    fn expression(&self, source_expr: source::Expr) -> canonical::Expr {
+    // the type being applied in a type application, which unlike the arguments may be a named type
+    // which hasn't been given all of its arguments yet.
+    fn type_constructor(&mut self, tipe: &source::Type) -> canonical::Type {
+        match &tipe.inner {
+            source::Type_::Identifier(name) => {
+                canonical::Type::Identifier(self.qualify_type(&tipe.region, name.to_owned()))
+            }
+            source::Type_::QualifiedIdentifier(module_name, name) => {
+                let module_name = self.module_name(module_name.clone());
+                self.foreign(&tipe.region, &module_name, name, Namespace::Type);
+                if module_name == self.module {
+                    return canonical::Type::Identifier(Qualified::Local(name.clone()));
+                }
+                canonical::Type::Identifier(Qualified::Foreign {
+                    module: module_name,
+                    member: name.clone(),
+                })
+            }
+            _ => self.tipe(tipe),
+        }
+    }
+
+    // every named type has to be given one argument for each of its type variables. Aliases rely
+    // on this to be expanded, and the type checker relies on it to never compare a type with a
+    // partially applied one.
+    fn type_arguments(&mut self, region: &Region, cons: &canonical::Type, found: usize) {
+        let canonical::Type::Identifier(name) = cons else {
+            return;
+        };
+        let expected = match name {
+            Qualified::Local(name) => match self.local_types.get(name) {
+                Some(tipe) => tipe.variables().len(),
+                None => return,
+            },
+            Qualified::Foreign { module, member } => {
+                match self
+                    .qualified_types
+                    .get(module)
+                    .and_then(|types| types.get(member))
+                {
+                    Some(tipe) => tipe.variables().len(),
+                    None => return,
+                }
+            }
+            Qualified::Kernel(_) => return,
+        };
+        let name = name.get().clone();
+        if found > expected {
+            self.errors.push(Error::Kind(kind::Error::TooManyArguments {
+                region: region.clone(),
+                name,
+                expected,
+                found,
+            }));
+        } else if found < expected {
+            self.errors.push(Error::Kind(kind::Error::TooFewArguments {
+                region: region.clone(),
+                name,
+                expected,
+                found,
+            }));
+        }
+    }
+
+    fn expression(&mut self, source_expr: source::Expr) -> canonical::Expr {
         // TODO: this doesn't generalize to custom subexpressions built in the canonicalization of operator expressions.
         let locate = |expr: canonical::Expr_| ast::Located {
             region: source_expr.region.clone(),
@@ -245,15 +795,87 @@ impl Environment {
             source::Expr_::External(name) => {
                 locate(canonical::Expr_::Variable(Qualified::Kernel(name)))
             }
-            source::Expr_::Let(pattern, expr, body) => match &pattern.inner {
-                source::Pattern_::Identifier(ident) => locate(canonical::Expr_::Let {
-                    name: ident.to_owned(),
-                    expr: Box::new(self.expression(*expr)),
-                    body: Box::new(self.expression(*body)),
-                }),
-                _ => todo!(),
-            },
-            source::Expr_::Bind(pattern, expr, expr1) => todo!(),
+            source::Expr_::Let(pattern, expr, body) => {
+                // a function is in scope of its own definition, so that local functions can be
+                // recursive. Any other value sees the binding it shadows, if there is one, and
+                // otherwise can't refer to itself at all.
+                let definition = pattern.region.merge(&expr.region);
+                let is_lambda = matches!(expr.inner, source::Expr_::Lambda(..));
+                let recursive = match &pattern.inner {
+                    source::Pattern_::Identifier(name) if is_lambda || !self.is_bound(name) => {
+                        Some(name.clone())
+                    }
+                    _ => None,
+                };
+                let depth = self.scopes.len();
+                self.local_references.retain(|(found, _)| *found < depth);
+                self.scopes
+                    .push(recursive.iter().cloned().collect::<HashSet<Name>>());
+                let expr = Box::new(self.expression(*expr));
+                self.scopes.pop();
+                let recursive =
+                    recursive.filter(|name| self.local_references.contains(&(depth, name.clone())));
+                if let (Some(name), false) = (&recursive, is_lambda) {
+                    self.errors.push(Error::Naming(naming::Error::CyclicValue {
+                        region: definition,
+                        name: name.clone(),
+                    }));
+                }
+
+                self.scopes.push(pattern.inner.bindings());
+                let body = Box::new(self.expression(*body));
+                self.scopes.pop();
+                match &pattern.inner {
+                    source::Pattern_::Identifier(ident) if recursive.is_some() => {
+                        locate(canonical::Expr_::LetRec {
+                            defs: vec![(ident.to_owned(), *expr)],
+                            body,
+                        })
+                    }
+                    source::Pattern_::Identifier(ident) => locate(canonical::Expr_::Let {
+                        name: ident.to_owned(),
+                        expr,
+                        body,
+                    }),
+                    _ => locate(canonical::Expr_::Destruct {
+                        pattern: self.pattern(&pattern),
+                        expr,
+                        body,
+                    }),
+                }
+            }
+            // `let x <- task; body` runs `task` and then `body` with its result, which is exactly
+            // what `Task.and_then` does.
+            source::Expr_::Bind(pattern, task, body) => {
+                let module = ModuleName(vec!["Task".to_owned()]);
+                let and_then = if self.module == module {
+                    self.references.insert("and_then".to_owned());
+                    Qualified::Local("and_then".to_owned())
+                } else {
+                    if !self.imported.contains(&module) {
+                        self.errors
+                            .push(Error::Naming(naming::Error::BindWithoutTask {
+                                region: pattern.region.merge(&task.region),
+                            }));
+                    }
+                    Qualified::Foreign {
+                        module,
+                        member: "and_then".to_owned(),
+                    }
+                };
+                let callback = self.expression(Located {
+                    region: source_expr.region.clone(),
+                    inner: source::Expr_::Lambda(pattern, body),
+                });
+                let task = self.expression(*task);
+                locate(canonical::Expr_::Ap {
+                    function: Box::new(locate(canonical::Expr_::Ap {
+                        function: Box::new(locate(canonical::Expr_::Variable(and_then))),
+                        arg: Box::new(callback),
+                    })),
+                    arg: Box::new(task),
+                })
+            }
             source::Expr_::If(cond, t, f) => locate(canonical::Expr_::If {
                 cond: Box::new(self.expression(*cond)),
                 true_branch: Box::new(self.expression(*t)),
@@ -263,23 +885,37 @@ impl Environment {
                 function: Box::new(self.expression(*expr)),
                 arg: Box::new(self.expression(*expr1)),
             }),
-            source::Expr_::Identifier(name) => {
-                // TODO: this would be where we determine
-                // a) whether this identifier is local or from another module and
-                // b) which module it comes from
-                locate(canonical::Expr_::Variable(Qualified::Local(name)))
+            source::Expr_::Identifier(name) => locate(canonical::Expr_::Variable(
+                self.variable(&source_expr.region, name),
+            )),
+            source::Expr_::Lambda(pattern, expr) => {
+                self.scopes.push(pattern.inner.bindings());
+                let body = Box::new(self.expression(*expr));
+                self.scopes.pop();
+                match &pattern.inner {
+                    source::Pattern_::Identifier(ident) => locate(canonical::Expr_::Lambda {
+                        arg: ident.to_owned(),
+                        body,
+                    }),
+                    source::Pattern_::Wildcard => locate(canonical::Expr_::Lambda {
+                        arg: "__wildcard".to_owned(),
+                        body,
+                    }),
+                    _ => locate(canonical::Expr_::Lambda {
+                        arg: "__arg".to_owned(),
+                        body: Box::new(locate(canonical::Expr_::Destruct {
+                            pattern: self.pattern(&pattern),
+                            expr: Box::new(Located {
+                                region: pattern.region.clone(),
+                                inner: canonical::Expr_::Variable(Qualified::Local(
+                                    "__arg".to_owned(),
+                                )),
+                            }),
+                            body,
+                        })),
+                    }),
+                }
             }
-            source::Expr_::Lambda(pattern, expr) => match &pattern.inner {
-                source::Pattern_::Identifier(ident) => locate(canonical::Expr_::Lambda {
-                    arg: ident.to_owned(),
-                    body: Box::new(self.expression(*expr)),
-                }),
-                source::Pattern_::Wildcard => locate(canonical::Expr_::Lambda {
-                    arg: "__wildcard".to_owned(),
-                    body: Box::new(self.expression(*expr)),
-                }),
-                _ => todo!(),
-            },
             source::Expr_::BinOp { op, lhs, rhs } => {
                 let lhs = Box::new(self.expression(*lhs));
                 let rhs = Box::new(self.expression(*rhs));
@@ -321,7 +957,7 @@ impl Environment {
                     source::Operator::Cons => locate(canonical::Expr_::Ap {
                         function: Box::new(locate(canonical::Expr_::Ap {
                             function: Box::new(locate(canonical::Expr_::Constructor(
-                                self.constructor("Cons".to_owned()),
+                                self.list_constructor(&source_expr.region, "Cons"),
                             ))),
                             arg: lhs,
                         })),
@@ -407,10 +1043,10 @@ impl Environment {
             }
             source::Expr_::When(expr, first, rest) => locate(canonical::Expr_::When {
                 expr: Box::new(self.expression(*expr)),
-                first_alternative: Box::new((self.pattern(&first.0), self.expression(first.1))),
+                first_alternative: Box::new(self.alternative(*first)),
                 rest_alternatives: rest
                     .into_iter()
-                    .map(|(pattern, body)| (self.pattern(&pattern), self.expression(body)))
+                    .map(|alternative| self.alternative(alternative))
                     .collect(),
             }),
             source::Expr_::Unit => locate(canonical::Expr_::Unit),
@@ -418,51 +1054,45 @@ impl Environment {
             source::Expr_::Int(int) => locate(canonical::Expr_::Int(int as f64)),
             source::Expr_::Float(float) => locate(canonical::Expr_::Float(float as f64)),
             source::Expr_::String(string) => locate(canonical::Expr_::String(string)),
-            source::Expr_::Record(hash_map) => todo!(),
+            source::Expr_::Record(fields) => locate(canonical::Expr_::Record(
+                fields
+                    .into_iter()
+                    .map(|(field, expr)| (field, self.expression(expr)))
+                    .collect(),
+            )),
+            source::Expr_::Access(record, field) => locate(canonical::Expr_::Access {
+                record: Box::new(self.expression(*record)),
+                field,
+            }),
+            source::Expr_::Update(record, fields) => locate(canonical::Expr_::Update {
+                record: Box::new(self.expression(*record)),
+                fields: fields
+                    .into_iter()
+                    .map(|(field, expr)| (field, self.expression(expr)))
+                    .collect(),
+            }),
             source::Expr_::QualifiedIdentifier(module, member) => {
+                let module = self.module_name(module);
+                self.foreign(&source_expr.region, &module, &member, Namespace::Value);
+                // the module's own definitions are referred to like unqualified ones, so that they
+                // are ordered and emitted the same way.
+                if module == self.module {
+                    self.references.insert(member.clone());
+                    return locate(canonical::Expr_::Variable(Qualified::Local(member)));
+                }
                 locate(canonical::Expr_::Variable(Qualified::Foreign {
                     module: module,
                     member: member,
                 }))
             }
             source::Expr_::List(exprs) => {
-                let type_str = "a".to_owned();
-                let type_var = Box::new(canonical::Type::Variable(type_str.clone()));
-                let list_type = canonical::Type::Application(
-                    Box::new(canonical::Type::Identifier(Qualified::Foreign {
-                        module: ModuleName(vec!["List".to_owned()]),
-                        member: "List".to_owned(),
-                    })),
-                    type_var.clone(),
-                );
+                let (empty, cons) = list_constructors();
                 let qualify = |constructor| Qualified::Foreign {
                     module: ModuleName(vec!["List".to_owned()]),
                     member: constructor,
                 };
-                let empty = canonical::Expr_::Constructor(qualify(canonical::Constructor {
-                    tag: 0,
-                    arity: 0,
-                    annotation: canonical::Annotation {
-                        quantified: HashSet::from([type_str.clone()]),
-                        tipe: list_type.clone(),
-                    },
-                }));
-                let cons = canonical::Expr_::Constructor(qualify(canonical::Constructor {
-                    tag: 1,
-                    arity: 2,
-                    annotation: {
-                        canonical::Annotation {
-                            quantified: HashSet::from([type_str.clone()]),
-                            tipe: canonical::Type::Lambda(
-                                type_var.clone(),
-                                Box::new(canonical::Type::Lambda(
-                                    Box::new(list_type.clone()),
-                                    Box::new(list_type.clone()),
-                                )),
-                            ),
-                        }
-                    },
-                }));
+                let empty = canonical::Expr_::Constructor(qualify(empty));
+                let cons = canonical::Expr_::Constructor(qualify(cons));
                 exprs.into_iter().rev().fold(locate(empty), |list, expr| {
                     locate(canonical::Expr_::Ap {
                         function: Box::new(locate(canonical::Expr_::Ap {
@@ -473,29 +1103,310 @@ impl Environment {
                     })
                 })
             }
-            source::Expr_::Constructor(name) => {
-                locate(canonical::Expr_::Constructor(self.constructor(name)))
-            }
-            source::Expr_::Tuple(exprs) => todo!(),
+            source::Expr_::Constructor(name) => locate(canonical::Expr_::Constructor(
+                self.constructor(&source_expr.region, name),
+            )),
+            source::Expr_::Tuple(exprs) => locate(canonical::Expr_::Tuple(
+                exprs
+                    .into_iter()
+                    .map(|expr| self.expression(expr))
+                    .collect(),
+            )),
             source::Expr_::QualifiedConstructor(module_name, name) => {
-                locate(canonical::Expr_::Variable(Qualified::Foreign {
-                    module: module_name,
+                let module_name = self.module_name(module_name);
+                locate(canonical::Expr_::Constructor(self.qualified_constructor(
+                    &source_expr.region,
+                    module_name,
+                    name,
+                )))
+            }
+        }
+    }
+
+    fn alternative(
+        &mut self,
+        (pattern, body): (source::Pattern, source::Expr),
+    ) -> (canonical::Pattern, canonical::Expr) {
+        self.scopes.push(pattern.inner.bindings());
+        let body = self.expression(body);
+        self.scopes.pop();
+        (self.pattern(&pattern), body)
+    }
+
+    // whether a name already means something here, without recording a reference to it.
+    fn is_bound(&self, name: &Name) -> bool {
+        self.scopes.iter().any(|scope| scope.contains(name))
+            || self.variables.contains(name)
+            || self.exposed_variables.contains_key(name)
+    }
+
+    // works out what an unqualified name refers to, looking through the enclosing scopes and then
+    // the module's own definitions before anything its imports expose.
+    fn variable(&mut self, region: &Region, name: Name) -> Qualified<Name> {
+        if let Some(depth) = self.scopes.iter().rposition(|scope| scope.contains(&name)) {
+            self.local_references.insert((depth, name.clone()));
+            return Qualified::Local(name);
+        }
+        if self.variables.contains(&name) {
+            self.references.insert(name.clone());
+            return Qualified::Local(name);
+        }
+        match self.exposed_variables.get(&name).map(Vec::as_slice) {
+            Some([module]) => {
+                return Qualified::Foreign {
+                    module: module.clone(),
                     member: name,
-                }))
+                }
             }
+            Some(modules) => {
+                let modules = modules.to_vec();
+                self.ambiguous(region, &name, modules);
+                return Qualified::Local(name);
+            }
+            None => {}
         }
+
+        let suggestions = util::suggestions(
+            &name,
+            self.scopes
+                .iter()
+                .flatten()
+                .chain(self.variables.iter())
+                .chain(self.exposed_variables.keys())
+                .map(|name| name.as_str()),
+        );
+        self.errors
+            .push(Error::Naming(naming::Error::UnboundVariable {
+                region: region.clone(),
+                name: name.clone(),
+                suggestions,
+            }));
+        Qualified::Local(name)
     }
 
-    fn constructor(&self, constructor: Name) -> Qualified<canonical::Constructor> {
+    fn constructor(
+        &mut self,
+        region: &Region,
+        constructor: Name,
+    ) -> Qualified<canonical::Constructor> {
         // CHECK FOR LOCAL
         if let Some(cons) = self.constructors.get(&constructor) {
-            Qualified::Local(cons.clone())
+            return Qualified::Local(cons.clone());
+        }
+
+        match self
+            .exposed_constructors
+            .get(&constructor)
+            .map(Vec::as_slice)
+        {
+            Some([module_name]) => {
+                return Qualified::Foreign {
+                    module: module_name.clone(),
+                    member: self.qualified_constructors[module_name][&constructor].clone(),
+                }
+            }
+            Some(modules) => {
+                let modules = modules.to_vec();
+                self.ambiguous(region, &constructor, modules);
+                return Qualified::Local(unresolved_constructor());
+            }
+            None => {}
+        }
+
+        match self.imported_member(region, &constructor, Namespace::Constructor) {
+            Some(module_name) => Qualified::Foreign {
+                member: self.qualified_constructors[&module_name][&constructor].clone(),
+                module: module_name,
+            },
+            None => Qualified::Local(unresolved_constructor()),
+        }
+    }
+
+    // `[]` and `::` always build and match `List.List`, whether or not `List` is imported, just like
+    // list literals.
+    fn list_constructor(
+        &mut self,
+        region: &Region,
+        name: &str,
+    ) -> Qualified<canonical::Constructor> {
+        let module = ModuleName(vec!["List".to_owned()]);
+        if self.module == module {
+            return self.constructor(region, name.to_owned());
+        }
+        let (empty, cons) = list_constructors();
+        Qualified::Foreign {
+            module,
+            member: if name == "Cons" { cons } else { empty },
+        }
+    }
+
+    fn qualified_constructor(
+        &mut self,
+        region: &Region,
+        module_name: ModuleName,
+        constructor: Name,
+    ) -> Qualified<canonical::Constructor> {
+        if !self.foreign(region, &module_name, &constructor, Namespace::Constructor) {
+            return Qualified::Foreign {
+                module: module_name,
+                member: unresolved_constructor(),
+            };
+        }
+
+        // `foreign` has checked that the constructor exists
+        if module_name == self.module {
+            return Qualified::Local(self.constructors[&constructor].clone());
+        }
+        let cons = self.qualified_constructors[&module_name][&constructor].clone();
+        Qualified::Foreign {
+            module: module_name,
+            member: cons,
+        }
+    }
+
+    // checks that a qualified name refers to a module which was loaded and imported, and to
+    // something that module actually has, recording an error if it doesn't.
+    fn foreign(
+        &mut self,
+        region: &Region,
+        module: &ModuleName,
+        name: &Name,
+        namespace: Namespace,
+    ) -> bool {
+        if !self.module_names.contains(module) {
+            let names = self
+                .module_names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<String>>();
+            let suggestions =
+                util::suggestions(&module.to_string(), names.iter().map(|name| name.as_str()));
+            self.errors
+                .push(Error::Naming(naming::Error::UnknownModule {
+                    region: region.clone(),
+                    module: module.clone(),
+                    suggestions,
+                }));
+            return false;
+        }
+        if *module != self.module && !self.imported.contains(module) {
+            self.errors.push(Error::Naming(naming::Error::NotImported {
+                region: region.clone(),
+                module: module.clone(),
+            }));
+            return false;
+        }
+
+        let members = self.members(module, namespace);
+        if members.contains(&name) {
+            if *module == self.module || self.exported(module, name, namespace) {
+                return true;
+            }
+            self.not_exported(region, module, name, namespace);
+            return false;
+        }
+
+        let suggestions = util::suggestions(name, members.into_iter().map(|name| name.as_str()));
+        self.errors
+            .push(Error::Naming(naming::Error::MissingMember {
+                region: region.clone(),
+                module: module.clone(),
+                name: name.clone(),
+                suggestions,
+            }));
+        false
+    }
+
+    fn not_exported(
+        &mut self,
+        region: &Region,
+        module: &ModuleName,
+        name: &Name,
+        namespace: Namespace,
+    ) {
+        // a constructor is most likely hidden because its type is exported without `(..)`.
+        let closed_type = match namespace {
+            Namespace::Constructor => self.modules.get(module).and_then(|canonical| {
+                canonical
+                    .unions
+                    .iter()
+                    .find(|(_, union)| union.variants.iter().any(|variant| variant.name == *name))
+                    .map(|(tipe, _)| tipe.clone())
+                    .filter(|tipe| self.exported(module, tipe, Namespace::Type))
+            }),
+            _ => None,
+        };
+        self.errors.push(Error::Naming(naming::Error::NotExported {
+            region: region.clone(),
+            module: module.clone(),
+            name: name.clone(),
+            closed_type,
+        }));
+    }
+
+    // everything the current module or one of its imports has in a namespace, exported or not.
+    fn members(&self, module: &ModuleName, namespace: Namespace) -> Vec<&Name> {
+        if *module == self.module {
+            match namespace {
+                Namespace::Value => self.variables.iter().collect(),
+                Namespace::Type => self.local_types.keys().collect(),
+                Namespace::Constructor => self.constructors.keys().collect(),
+            }
         } else {
-            todo!("cannot find constructor {}", constructor)
+            // modules are canonicalized after their imports, so an imported module always has
+            // been.
+            match namespace {
+                Namespace::Value => self.qualified_variables[module].iter().collect(),
+                Namespace::Type => self.qualified_types[module].keys().collect(),
+                Namespace::Constructor => self.qualified_constructors[module].keys().collect(),
+            }
         }
     }
 
-    fn alias(&self, alias: &source::Alias) -> canonical::Alias {
+    // whether another module's export list makes one of its members public. Constructors are only
+    // public when their type is exported with `(..)`, so that a closed type can't be built or
+    // taken apart anywhere but its own module.
+    fn exported(&self, module: &ModuleName, name: &Name, namespace: Namespace) -> bool {
+        let Some(module) = self.modules.get(module) else {
+            return false;
+        };
+        module
+            .exports
+            .iter()
+            .any(|export| match (namespace, export) {
+                (Namespace::Value, canonical::Export::Value(value)) => value == name,
+                (
+                    Namespace::Type,
+                    canonical::Export::ClosedType(tipe) | canonical::Export::OpenType(tipe),
+                ) => tipe == name,
+                (Namespace::Constructor, canonical::Export::OpenType(tipe)) => {
+                    module.unions.get(tipe).is_some_and(|union| {
+                        union.variants.iter().any(|variant| variant.name == *name)
+                    })
+                }
+                _ => false,
+            })
+    }
+
+    fn alias(&mut self, name: &Name, alias: &source::Alias) -> canonical::Alias {
+        // a variable the alias doesn't take as a parameter would stand for a different type at
+        // each use, just like one in a union's constructor.
+        let mut free = alias
+            .other
+            .inner
+            .free_variables()
+            .into_iter()
+            .collect::<Vec<Name>>();
+        free.sort();
+        if let Some(variable) = free.into_iter().find(|var| !alias.variables.contains(var)) {
+            self.errors
+                .push(Error::Type(tipe::Error::UnboundTypeVariable {
+                    region: alias.other.region.clone(),
+                    tipe: name.clone(),
+                    variable,
+                }));
+        }
+
         canonical::Alias {
             variables: alias.variables.clone(),
             other: self.tipe(&alias.other),
@@ -503,34 +1414,52 @@ impl Environment {
     }
 
     fn union_artifacts(
-        &self,
+        &mut self,
         name: Name,
         union: &source::Union,
     ) -> (canonical::Union, HashMap<Name, canonical::Constructor>) {
+        let union = canonical::Union {
+            variables: union.variables.clone(),
+            variants: union
+                .variants
+                .iter()
+                .map(|variant| canonical::Variant {
+                    name: variant.name.inner.clone(),
+                    args: variant
+                        .args
+                        .iter()
+                        .map(|arg| Located {
+                            region: arg.region.clone(),
+                            inner: self.tipe(arg),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+
         let constructors = union
             .variants
-            .clone()
-            .into_iter()
+            .iter()
             .enumerate()
-            .map(|(i, constructor)| {
+            .map(|(i, variant)| {
                 (
-                    constructor.name,
+                    variant.name.clone(),
                     canonical::Constructor {
                         tag: i as u16,
-                        arity: constructor.args.len() as u16,
+                        arity: variant.args.len() as u16,
                         annotation: {
-                            let tipe = constructor
-                                .args
-                                .clone()
-                                .into_iter()
-                                .map(|arg| self.tipe(&arg))
-                                .rev()
-                                .fold(
+                            let tipe = variant.args.iter().map(|arg| arg.inner.clone()).rev().fold(
+                                union.variables.iter().fold(
                                     canonical::Type::Identifier(Qualified::Local(name.clone())),
-                                    |ret, arg| {
-                                        canonical::Type::Lambda(Box::new(arg), Box::new(ret))
+                                    |cons, var| {
+                                        canonical::Type::Application(
+                                            Box::new(cons),
+                                            Box::new(canonical::Type::Variable(var.clone())),
+                                        )
                                     },
-                                );
+                                ),
+                                |ret, arg| canonical::Type::Lambda(Box::new(arg), Box::new(ret)),
+                            );
 
                             canonical::Annotation {
                                 quantified: tipe.free_variables(),
@@ -542,38 +1471,43 @@ impl Environment {
             })
             .collect::<HashMap<Name, canonical::Constructor>>();
 
-        let union = canonical::Union {
-            variables: union.variables.clone(),
-            constructors: constructors.keys().map(|k| k.clone()).collect(),
-        };
-
         (union, constructors)
     }
 
-    fn pattern(&self, pattern: &source::Pattern) -> canonical::Pattern {
+    fn pattern(&mut self, pattern: &source::Pattern) -> canonical::Pattern {
         Located {
             region: pattern.region.clone(),
             inner: match &pattern.inner {
                 source::Pattern_::Wildcard => canonical::Pattern_::Wildcard,
                 source::Pattern_::Identifier(name) => canonical::Pattern_::Identifier(name.clone()),
                 source::Pattern_::Constructor(name, args) => canonical::Pattern_::Constructor(
-                    self.constructors.get(name).unwrap().clone(),
+                    self.constructor(&pattern.region, name.clone()),
                     args.clone()
                         .into_iter()
                         .map(|arg| self.pattern(&arg))
                         .collect::<Vec<canonical::Pattern>>(),
                 ),
-                source::Pattern_::Cons(element, list) => {
-                    self.pattern(&source::Pattern {
-                        region: pattern.region.clone(),
-                        inner: source::Pattern_::Constructor(
-                            "Cons".to_owned(),
-                            vec![*element.clone(), *list.clone()],
-                        ),
-                    })
-                    .inner
+                source::Pattern_::QualifiedConstructor(module_name, name, args) => {
+                    let module_name = self.module_name(module_name.clone());
+                    canonical::Pattern_::Constructor(
+                        self.qualified_constructor(&pattern.region, module_name, name.clone()),
+                        args.iter().map(|arg| self.pattern(arg)).collect(),
+                    )
                 }
-                source::Pattern_::Tuple(locateds) => todo!(),
+                source::Pattern_::EmptyList => canonical::Pattern_::Constructor(
+                    self.list_constructor(&pattern.region, "Empty"),
+                    vec![],
+                ),
+                source::Pattern_::Cons(element, list) => canonical::Pattern_::Constructor(
+                    self.list_constructor(&pattern.region, "Cons"),
+                    vec![self.pattern(element), self.pattern(list)],
+                ),
+                source::Pattern_::Tuple(patterns) => canonical::Pattern_::Tuple(
+                    patterns
+                        .iter()
+                        .map(|pattern| self.pattern(pattern))
+                        .collect(),
+                ),
             },
         }
     }
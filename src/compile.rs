@@ -1,4 +1,4 @@
-use crate::ast::{optimized::*, ModuleName, Qualified};
+use crate::ast::{optimized::*, ModuleName, Name, Qualified};
 use crate::util::{indent, to_camel_case};
 
 use std::collections::HashMap;
@@ -39,7 +39,7 @@ impl ToJs for Expr {
             Expr::Float(num) => num.to_string(),
             Expr::Identifier(identifier) => match identifier {
                 Qualified::Foreign { module, member } => {
-                    format!("{}.{}", module, to_camel_case(member))
+                    format!("{}.{}", namespace(module), to_camel_case(member))
                 }
                 Qualified::Local(name) => to_camel_case(name).to_owned(),
                 Qualified::Kernel(name) => name.clone(),
@@ -75,29 +75,52 @@ impl ToJs for Expr {
                         .join(", ")
                     + "]"
             }
+            // tuples are laid out like a constructor with a single variant, so that `when`
+            // expressions can look inside them the same way.
+            Expr::Tuple(exprs) => format!(
+                "({{ tag: 0, arity: {}, args: [{}] }})",
+                exprs.len(),
+                exprs
+                    .iter()
+                    .map(|expr| expr.to_js())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
             Expr::Extern(string) => match &string[..] {
-                "println" => "console.log".to_owned(),
+                // a task is a function which performs its effect when it's called, so that nothing
+                // happens until the runtime calls `main`.
+                "println" => "((string) => () => console.log(string))".to_owned(),
+                "Task.succeed" => "((value) => () => value)".to_owned(),
+                "Task.map" => "((f) => (task) => () => f(task()))".to_owned(),
+                "Task.and_then" => "((f) => (task) => () => f(task())())".to_owned(),
                 "crash" => "console.error".to_owned(),
                 "to_string" => "((x) =>
     (typeof x === 'number') ? '' + x
     : (typeof x === 'string') ? '\"' + x + '\"'
+    : (typeof x === 'boolean') ? (x ? 'True' : 'False')
     : ('tag' in x) ? 'pack_' + x.tag + '_' + x.arity + '(' + x.args.map(toString).join(', ') + ')'
     : ''
 )"
                 .to_owned(),
                 _ => string.to_owned(),
             },
-            Expr::Lambda { arg, body } => "(".to_owned() + &arg + ") => " + &body.to_js(),
+            Expr::Lambda { arg, body } => {
+                "(".to_owned()
+                    + &to_camel_case(arg)
+                    + ") => "
+                    + &(if needs_block(body) {
+                        "{\n".to_owned() + &indent(&block(body, 0)) + "\n}"
+                    } else {
+                        body.to_js()
+                    })
+            }
             Expr::Op { op, lhs, rhs } => {
-                "(".to_owned() + &lhs.to_js() + " " + &op.to_js() + " " + &rhs.to_js() + ")"
+                "(".to_owned() + &operand(lhs) + " " + &op.to_js() + " " + &operand(rhs) + ")"
+            }
+            Expr::Ap { function, arg } => operand(function) + "(" + &arg.to_js() + ")",
+            Expr::Let { .. } | Expr::LetRec { .. } | Expr::When { .. } => {
+                "(() => {\n".to_owned() + &indent(&block(self, 0)) + "\n})()"
             }
-            Expr::When {
-                expr,
-                decision_tree,
-            } => todo!("compile when expressions"),
-            Expr::Ap { function, arg } => function.to_js() + "(" + &arg.to_js() + ")",
-            Expr::Let { name, expr, body } => todo!(),
-            Expr::LetRec { defs, body } => todo!(),
             Expr::If {
                 cond,
                 true_branch,
@@ -109,27 +132,224 @@ impl ToJs for Expr {
                 false_branch.to_js()
             ),
             Expr::Bool(bool) => (if *bool { "true" } else { "false" }).to_owned(),
+            Expr::Record(fields) => "({ ".to_owned() + &fields_to_js(fields) + " })",
+            Expr::Access { record, field } => record.to_js() + "." + &to_camel_case(field),
+            Expr::Update { record, fields } => {
+                "({ ...".to_owned() + &record.to_js() + ", " + &fields_to_js(fields) + " })"
+            }
+        }
+    }
+}
+
+fn fields_to_js(fields: &[(Name, Expr)]) -> String {
+    fields
+        .iter()
+        .map(|(field, expr)| to_camel_case(field) + ": " + &expr.to_js())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+// an arrow function's body extends as far to the right as it can, so one which is called or used
+// as an operand needs parentheses around it: `((x) => x + 1)(2)`, not `(x) => x + 1(2)`.
+fn operand(expr: &Expr) -> String {
+    match expr {
+        Expr::Lambda { .. } => "(".to_owned() + &expr.to_js() + ")",
+        _ => expr.to_js(),
+    }
+}
+
+fn needs_block(expr: &Expr) -> bool {
+    matches!(
+        expr,
+        Expr::Let { .. } | Expr::LetRec { .. } | Expr::When { .. }
+    )
+}
+
+// `let` and `when` expressions need statements, so they are written out as the body of a block
+// which ends by returning the value of the whole expression. `depth` counts how many `let` and
+// `when` expressions this block is nested inside, so that each one gets its own variable names.
+//
+// a name can be bound again by an inner `let` or pattern, which JS doesn't allow within the same
+// scope, so each of them opens a new block. A `let`'s value still has to see the name it shadows,
+// so it is worked out before the block is opened.
+fn block(expr: &Expr, depth: usize) -> String {
+    match expr {
+        Expr::Let { name, expr, body } => format!(
+            "const __let{} = {};\n{{\n{}\n}}",
+            depth,
+            expr.to_js(),
+            indent(&format!(
+                "const {} = __let{};\n{}",
+                to_camel_case(name),
+                depth,
+                block(body, depth + 1)
+            ))
+        ),
+        Expr::LetRec { defs, body } => format!(
+            "{{\n{}\n}}",
+            indent(
+                &(defs
+                    .iter()
+                    .map(|(name, def)| {
+                        format!("const {} = {};\n", to_camel_case(name), def.to_js())
+                    })
+                    .collect::<String>()
+                    + &block(body, depth + 1))
+            )
+        ),
+        Expr::When {
+            expr,
+            decision_tree,
+            branches,
+        } => {
+            let subject = format!("__when{}", depth);
+            let mut leaves = vec![0; branches.len()];
+            count_leaves(decision_tree, &mut leaves);
+
+            // branches which are reached from more than one leaf are only written out once, as a
+            // function which each of those leaves calls.
+            let shared = branches
+                .iter()
+                .enumerate()
+                .filter(|(index, _)| leaves[*index] > 1)
+                .map(|(index, branch)| {
+                    format!(
+                        "const __branch{}_{} = () => {{\n{}\n}};\n",
+                        depth,
+                        index,
+                        indent(&branch_body(branch, &subject, depth))
+                    )
+                })
+                .collect::<String>();
+
+            format!(
+                "const {} = {};\n{}{}",
+                subject,
+                expr.to_js(),
+                shared,
+                switch(decision_tree, &subject, branches, &leaves, depth)
+            )
+        }
+        _ => format!("return {};", expr.to_js()),
+    }
+}
+
+fn count_leaves(decision_tree: &DecisionTree<usize>, leaves: &mut Vec<usize>) {
+    match decision_tree {
+        DecisionTree::Succeed(index) => leaves[*index] += 1,
+        DecisionTree::Switch {
+            edges, fallback, ..
+        } => {
+            for (_, tree) in edges.iter() {
+                count_leaves(tree, leaves);
+            }
+            if let Some(tree) = fallback {
+                count_leaves(tree, leaves);
+            }
+        }
+    }
+}
+
+fn switch(
+    decision_tree: &DecisionTree<usize>,
+    subject: &str,
+    branches: &[Branch],
+    leaves: &[usize],
+    depth: usize,
+) -> String {
+    match decision_tree {
+        DecisionTree::Succeed(index) if leaves[*index] > 1 => {
+            format!("return __branch{}_{}();", depth, index)
         }
+        DecisionTree::Succeed(index) => branch_body(&branches[*index], subject, depth),
+        DecisionTree::Switch {
+            path,
+            edges,
+            fallback,
+        } => {
+            let mut cases = edges
+                .iter()
+                .map(|(test, tree)| {
+                    let case = match test {
+                        Test::IsConstructor(tag) => tag.to_string(),
+                        Test::IsBool(bool) => bool.to_string(),
+                    };
+                    format!(
+                        "case {}: {{\n{}\n}}",
+                        case,
+                        indent(&switch(tree, subject, branches, leaves, depth))
+                    )
+                })
+                .collect::<Vec<String>>();
+            if let Some(tree) = fallback {
+                cases.push(format!(
+                    "default: {{\n{}\n}}",
+                    indent(&switch(tree, subject, branches, leaves, depth))
+                ));
+            }
+            // a boolean is switched on directly, since it has no tag.
+            let on = match edges.first() {
+                Some((Test::IsBool(_), _)) => path_to_js(subject, path),
+                _ => path_to_js(subject, path) + ".tag",
+            };
+            format!("switch ({}) {{\n{}\n}}", on, indent(&cases.join("\n")))
+        }
+    }
+}
+
+fn branch_body(branch: &Branch, subject: &str, depth: usize) -> String {
+    let body = block(&branch.body, depth + 1);
+    if branch.bindings.is_empty() {
+        return body;
+    }
+
+    let bindings = branch
+        .bindings
+        .iter()
+        .map(|(name, path)| {
+            format!(
+                "const {} = {};\n",
+                to_camel_case(name),
+                path_to_js(subject, path)
+            )
+        })
+        .collect::<String>();
+    format!("{{\n{}\n}}", indent(&(bindings + &body)))
+}
+
+fn path_to_js(subject: &str, path: &Path) -> String {
+    match path {
+        Path::Root => subject.to_owned(),
+        Path::Index(index, path) => format!("{}.args[{}]", path_to_js(subject, path), index),
     }
 }
 
 const BUILD_DIR: &str = "build/js/";
 
+// the name an imported module goes by in JS, where identifiers can't contain dots.
+fn namespace(module_name: &ModuleName) -> String {
+    module_name.0.join("$")
+}
+
 fn compile_module(
     module_name: ModuleName,
     module: Module,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let mut file = File::create(&(BUILD_DIR.to_owned() + &format!("{}", module_name) + ".js"))?;
     if module_name != ModuleName(vec!["Basics".to_owned()]) {
-        file.write("import { toString } from \"./Basics.js\";\n".as_bytes());
+        file.write_all("import { toString } from \"./Basics.js\";\n".as_bytes());
     }
 
     for import in &module.imports {
-        let js = format!("import * as {i} from \"./{i}.js\";\n", i = &import);
-        file.write(js.as_bytes()).unwrap();
+        let js = format!(
+            "import * as {} from \"./{}.js\";\n",
+            namespace(import),
+            import
+        );
+        file.write_all(js.as_bytes()).unwrap();
     }
 
-    file.write("\n".as_bytes());
+    file.write_all("\n".as_bytes());
 
     for (binding, body) in &module.definitions {
         let export = if module.exports.contains(binding) {
@@ -143,15 +363,19 @@ fn compile_module(
             + " = "
             + &body.to_js()
             + ";\n\n";
-        file.write(js.as_bytes()).unwrap();
+        file.write_all(js.as_bytes()).unwrap();
     }
 
     Ok(())
 }
 
+// whether a module has been compiled before, in which case it may not need compiling again.
+pub fn is_compiled(module_name: &ModuleName) -> bool {
+    std::path::Path::new(&format!("{}{}.js", BUILD_DIR, module_name)).exists()
+}
+
 pub fn compile(modules: HashMap<ModuleName, Module>) -> Result<(), Box<dyn std::error::Error>> {
-    std::fs::remove_dir_all(BUILD_DIR);
-    std::fs::create_dir(BUILD_DIR);
+    std::fs::create_dir_all(BUILD_DIR)?;
 
     for (module_name, module) in modules {
         println!("compiling {}.pac", module_name);
@@ -159,3 +383,221 @@ pub fn compile(modules: HashMap<ModuleName, Module>) -> Result<(), Box<dyn std::
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::ast::{canonical, Located, Region};
+
+    fn local(name: &str) -> Expr {
+        Expr::Identifier(Qualified::Local(name.to_owned()))
+    }
+
+    fn plus(lhs: Expr, rhs: Expr) -> Expr {
+        Expr::Op {
+            op: Operator::Plus,
+            lhs: Box::new(lhs),
+            rhs: Box::new(rhs),
+        }
+    }
+
+    fn let_(name: &str, expr: Expr, body: Expr) -> Expr {
+        Expr::Let {
+            name: name.to_owned(),
+            expr: Box::new(expr),
+            body: Box::new(body),
+        }
+    }
+
+    #[test]
+    fn shadowing_lets_get_their_own_blocks() {
+        // let x = 1; let x = x + 1; x
+        let expr = let_(
+            "x",
+            Expr::Int(1.0),
+            let_("x", plus(local("x"), Expr::Int(1.0)), local("x")),
+        );
+        assert_eq!(
+            expr.to_js(),
+            "(() => {
+    const __let0 = 1;
+    {
+        const x = __let0;
+        const __let1 = (x + 1);
+        {
+            const x = __let1;
+            return x;
+        }
+    }
+})()"
+        );
+    }
+
+    #[test]
+    fn pattern_bindings_can_shadow_an_argument() {
+        // \x -> when x is | (x, _) -> x;
+        let expr = Expr::Lambda {
+            arg: "x".to_owned(),
+            body: Box::new(Expr::When {
+                expr: Box::new(local("x")),
+                decision_tree: Box::new(DecisionTree::Succeed(0)),
+                branches: vec![Branch {
+                    bindings: vec![("x".to_owned(), Path::Index(0, Box::new(Path::Root)))],
+                    body: local("x"),
+                }],
+            }),
+        };
+        assert_eq!(
+            expr.to_js(),
+            "(x) => {
+    const __when0 = x;
+    {
+        const x = __when0.args[0];
+        return x;
+    }
+}"
+        );
+    }
+
+    fn canonical(inner: canonical::Expr_) -> canonical::Expr {
+        Located {
+            region: Region::ZERO,
+            inner,
+        }
+    }
+
+    fn boolean(value: bool) -> Qualified<canonical::Constructor> {
+        Qualified::Foreign {
+            module: ModuleName(vec!["Bool".to_owned()]),
+            member: canonical::Constructor {
+                tag: if value { 0 } else { 1 },
+                arity: 0,
+                annotation: canonical::Annotation {
+                    quantified: HashSet::new(),
+                    tipe: canonical::Type::Identifier(Qualified::Local("Bool".to_owned())),
+                },
+            },
+        }
+    }
+
+    fn less_than(lhs: f64, rhs: f64) -> canonical::Expr {
+        canonical(canonical::Expr_::Op {
+            op: canonical::Operator::LT,
+            lhs: Box::new(canonical(canonical::Expr_::Int(lhs))),
+            rhs: Box::new(canonical(canonical::Expr_::Int(rhs))),
+        })
+    }
+
+    fn string(value: &str) -> canonical::Expr {
+        canonical(canonical::Expr_::String(value.to_owned()))
+    }
+
+    // `when subject is | True -> yes | False -> no;`
+    fn when_bool(
+        subject: canonical::Expr,
+        yes: canonical::Expr,
+        no: canonical::Expr,
+    ) -> canonical::Expr {
+        let pattern = |value| Located {
+            region: Region::ZERO,
+            inner: canonical::Pattern_::Constructor(boolean(value), vec![]),
+        };
+        canonical(canonical::Expr_::When {
+            expr: Box::new(subject),
+            first_alternative: Box::new((pattern(true), yes)),
+            rest_alternatives: vec![(pattern(false), no)],
+        })
+    }
+
+    fn module(
+        unions: HashMap<Name, canonical::Union>,
+        definitions: Vec<canonical::Definitions>,
+    ) -> canonical::Module {
+        canonical::Module {
+            unions,
+            aliases: HashMap::new(),
+            external_types: HashMap::new(),
+            constructors: HashMap::new(),
+            definitions,
+            imports: vec![],
+            exports: vec![],
+        }
+    }
+
+    // optimizes and compiles `expr` alongside a `Bool` module, then runs it with node. Returns
+    // `None` when node isn't installed.
+    fn run(expr: canonical::Expr) -> Option<String> {
+        let variant = |name: &str| canonical::Variant {
+            name: name.to_owned(),
+            args: vec![],
+        };
+        let bool = canonical::Union {
+            variables: vec![],
+            variants: vec![variant("True"), variant("False")],
+        };
+        let main = canonical::Definitions::NonRecursive(canonical::Definition {
+            annotation: None,
+            name: "main".to_owned(),
+            expr,
+        });
+        let modules = HashMap::from([
+            (
+                ModuleName(vec!["Bool".to_owned()]),
+                module(HashMap::from([("Bool".to_owned(), bool)]), vec![]),
+            ),
+            (
+                ModuleName(vec!["Main".to_owned()]),
+                module(HashMap::new(), vec![main]),
+            ),
+        ]);
+        let optimized = crate::optimize::optimize(&modules);
+        let (_, main) = &optimized[&ModuleName(vec!["Main".to_owned()])].definitions[0];
+
+        let output = std::process::Command::new("node")
+            .arg("-e")
+            .arg(format!("console.log({})", main.to_js()))
+            .output()
+            .ok()?;
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    }
+
+    #[test]
+    fn comparisons_and_bool_constructors_are_the_same_values() {
+        // when 1 < 2 is | True -> "lt" | False -> "ge";
+        let compared = when_bool(less_than(1.0, 2.0), string("lt"), string("ge"));
+        // if (when True is | True -> False | False -> True;) then "wrong" else "right"
+        let negated = canonical(canonical::Expr_::If {
+            cond: Box::new(when_bool(
+                canonical(canonical::Expr_::Constructor(boolean(true))),
+                canonical(canonical::Expr_::Constructor(boolean(false))),
+                canonical(canonical::Expr_::Constructor(boolean(true))),
+            )),
+            true_branch: Box::new(string("wrong")),
+            false_branch: Box::new(string("right")),
+        });
+        // when (2 < 1) == False is | True -> "equal" | False -> "different";
+        let equal = when_bool(
+            canonical(canonical::Expr_::Op {
+                op: canonical::Operator::Eq,
+                lhs: Box::new(less_than(2.0, 1.0)),
+                rhs: Box::new(canonical(canonical::Expr_::Constructor(boolean(false)))),
+            }),
+            string("equal"),
+            string("different"),
+        );
+
+        let Some(compared) = run(compared) else {
+            return;
+        };
+        assert_eq!(compared, "lt");
+        assert_eq!(run(negated).unwrap(), "right");
+        assert_eq!(run(equal).unwrap(), "equal");
+    }
+}
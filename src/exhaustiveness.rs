@@ -0,0 +1,528 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{
+        canonical::{self, Constructor, Expr, Expr_},
+        source, Located, ModuleName, Name, Qualified, Region,
+    },
+    report::error::pattern::Error,
+};
+
+// checks that every `when` expression covers all possible values and that none of its branches
+// are unreachable. See "Warnings for pattern matching" by Luc Maranget for the algorithm.
+
+pub fn check(
+    modules: &HashMap<ModuleName, canonical::Module>,
+) -> Result<(), Vec<(ModuleName, Error)>> {
+    let mut names = modules.keys().cloned().collect::<Vec<ModuleName>>();
+    names.sort_by_key(|name| name.to_string());
+
+    let mut errors = vec![];
+    for module_name in names {
+        let checker = Checker {
+            module: &module_name,
+            modules,
+        };
+        let mut module_errors = vec![];
+        for definitions in modules[&module_name].definitions.iter() {
+            match definitions {
+                canonical::Definitions::Recursive(definitions) => {
+                    for definition in definitions.iter() {
+                        checker.expression(&definition.expr, &mut module_errors);
+                    }
+                }
+                canonical::Definitions::NonRecursive(definition) => {
+                    checker.expression(&definition.expr, &mut module_errors)
+                }
+            }
+        }
+        errors.extend(
+            module_errors
+                .into_iter()
+                .map(|error| (module_name.clone(), error)),
+        );
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Pattern {
+    Anything,
+    Constructor {
+        union: Union,
+        tag: usize,
+        args: Vec<Pattern>,
+    },
+}
+
+#[derive(Debug, Clone)]
+struct Union {
+    kind: UnionKind,
+    alternatives: Vec<(Name, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum UnionKind {
+    Custom,
+    List,
+    Tuple,
+}
+
+struct Checker<'a> {
+    module: &'a ModuleName,
+    modules: &'a HashMap<ModuleName, canonical::Module>,
+}
+
+impl<'a> Checker<'a> {
+    fn expression(&self, expr: &Expr, errors: &mut Vec<Error>) {
+        match &expr.inner {
+            Expr_::Variable(_)
+            | Expr_::Constructor(_)
+            | Expr_::Unit
+            | Expr_::Bool(_)
+            | Expr_::Int(_)
+            | Expr_::Float(_)
+            | Expr_::String(_) => {}
+            Expr_::List(exprs) | Expr_::Tuple(exprs) => {
+                for expr in exprs.iter() {
+                    self.expression(expr, errors);
+                }
+            }
+            Expr_::Ap { function, arg } => {
+                self.expression(function, errors);
+                self.expression(arg, errors);
+            }
+            Expr_::Op { lhs, rhs, .. } => {
+                self.expression(lhs, errors);
+                self.expression(rhs, errors);
+            }
+            Expr_::Let { expr, body, .. } => {
+                self.expression(expr, errors);
+                self.expression(body, errors);
+            }
+            Expr_::Destruct {
+                pattern,
+                expr,
+                body,
+            } => {
+                self.expression(expr, errors);
+                self.expression(body, errors);
+                let missing = missing(&[vec![self.simplify(pattern)]], 1);
+                if !missing.is_empty() {
+                    errors.push(Error::Refutable {
+                        region: pattern.region.clone(),
+                        missing: missing
+                            .into_iter()
+                            .map(|mut row| example(row.remove(0)))
+                            .collect(),
+                    });
+                }
+            }
+            Expr_::LetRec { defs, body } => {
+                for (_, def) in defs.iter() {
+                    self.expression(def, errors);
+                }
+                self.expression(body, errors);
+            }
+            Expr_::Lambda { body, .. } => self.expression(body, errors),
+            Expr_::If {
+                cond,
+                true_branch,
+                false_branch,
+            } => {
+                self.expression(cond, errors);
+                self.expression(true_branch, errors);
+                self.expression(false_branch, errors);
+            }
+            Expr_::When {
+                expr: subject,
+                first_alternative,
+                rest_alternatives,
+            } => {
+                self.expression(subject, errors);
+                let alternatives = std::iter::once(&**first_alternative)
+                    .chain(rest_alternatives.iter())
+                    .collect::<Vec<_>>();
+                for (_, body) in alternatives.iter() {
+                    self.expression(body, errors);
+                }
+                self.when(&expr.region, &alternatives, errors);
+            }
+            Expr_::Record(fields) => {
+                for (_, expr) in fields.iter() {
+                    self.expression(expr, errors);
+                }
+            }
+            Expr_::Access { record, .. } => self.expression(record, errors),
+            Expr_::Update { record, fields } => {
+                self.expression(record, errors);
+                for (_, expr) in fields.iter() {
+                    self.expression(expr, errors);
+                }
+            }
+        }
+    }
+
+    fn when(
+        &self,
+        region: &Region,
+        alternatives: &[&(canonical::Pattern, Expr)],
+        errors: &mut Vec<Error>,
+    ) {
+        let mut matrix: Vec<Vec<Pattern>> = vec![];
+        for (index, (pattern, _)) in alternatives.iter().enumerate() {
+            let row = vec![self.simplify(pattern)];
+            if !is_useful(&matrix, &row) {
+                errors.push(Error::Redundant {
+                    region: pattern.region.clone(),
+                    index: index + 1,
+                });
+            }
+            matrix.push(row);
+        }
+
+        let missing = missing(&matrix, 1);
+        if !missing.is_empty() {
+            errors.push(Error::Incomplete {
+                region: region.clone(),
+                missing: missing
+                    .into_iter()
+                    .map(|mut row| example(row.remove(0)))
+                    .collect(),
+            });
+        }
+    }
+
+    fn simplify(&self, pattern: &canonical::Pattern) -> Pattern {
+        match &pattern.inner {
+            canonical::Pattern_::Wildcard | canonical::Pattern_::Identifier(_) => Pattern::Anything,
+            canonical::Pattern_::Constructor(constructor, args) => Pattern::Constructor {
+                union: self.union(constructor),
+                tag: constructor.get().tag as usize,
+                args: args.iter().map(|arg| self.simplify(arg)).collect(),
+            },
+            canonical::Pattern_::Tuple(patterns) => Pattern::Constructor {
+                union: Union {
+                    kind: UnionKind::Tuple,
+                    alternatives: vec![("".to_owned(), patterns.len())],
+                },
+                tag: 0,
+                args: patterns
+                    .iter()
+                    .map(|pattern| self.simplify(pattern))
+                    .collect(),
+            },
+        }
+    }
+
+    fn union(&self, constructor: &Qualified<Constructor>) -> Union {
+        let home = match constructor {
+            Qualified::Foreign { module, .. } => module,
+            Qualified::Local(_) | Qualified::Kernel(_) => self.module,
+        };
+        let (home, name) = match constructor.get().union() {
+            Qualified::Foreign { module, member } => (module, member),
+            Qualified::Local(name) | Qualified::Kernel(name) => (home, name),
+        };
+
+        let union = &self.modules[home].unions[name];
+        Union {
+            kind: if home.0 == ["List"] && name == "List" {
+                UnionKind::List
+            } else {
+                UnionKind::Custom
+            },
+            alternatives: union
+                .variants
+                .iter()
+                .map(|variant| (variant.name.clone(), variant.args.len()))
+                .collect(),
+        }
+    }
+}
+
+// USEFULNESS
+
+fn is_useful(matrix: &[Vec<Pattern>], vector: &[Pattern]) -> bool {
+    let Some((first, rest)) = vector.split_first() else {
+        return matrix.is_empty();
+    };
+
+    match first {
+        Pattern::Constructor { tag, args, .. } => {
+            let specialized = specialize(matrix, *tag, args.len());
+            is_useful(&specialized, &[args.as_slice(), rest].concat())
+        }
+        Pattern::Anything => match complete_union(matrix) {
+            Some(union) => union
+                .alternatives
+                .iter()
+                .enumerate()
+                .any(|(tag, (_, arity))| {
+                    let specialized = specialize(matrix, tag, *arity);
+                    is_useful(
+                        &specialized,
+                        &[vec![Pattern::Anything; *arity].as_slice(), rest].concat(),
+                    )
+                }),
+            None => is_useful(&default(matrix), rest),
+        },
+    }
+}
+
+// returns one row of example patterns for every group of values the matrix doesn't cover.
+fn missing(matrix: &[Vec<Pattern>], width: usize) -> Vec<Vec<Pattern>> {
+    if matrix.is_empty() {
+        return vec![vec![Pattern::Anything; width]];
+    }
+    if width == 0 {
+        return vec![];
+    }
+
+    let tags = first_tags(matrix);
+    let Some(union) = first_union(matrix) else {
+        return missing(&default(matrix), width - 1)
+            .into_iter()
+            .map(|row| [vec![Pattern::Anything], row].concat())
+            .collect();
+    };
+
+    if tags.len() == union.alternatives.len() {
+        union
+            .alternatives
+            .iter()
+            .enumerate()
+            .flat_map(|(tag, (_, arity))| {
+                missing(&specialize(matrix, tag, *arity), arity + width - 1)
+                    .into_iter()
+                    .map(|mut row| {
+                        let rest = row.split_off(*arity);
+                        [
+                            vec![Pattern::Constructor {
+                                union: union.clone(),
+                                tag,
+                                args: row,
+                            }],
+                            rest,
+                        ]
+                        .concat()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    } else {
+        let absent = union
+            .alternatives
+            .iter()
+            .enumerate()
+            .filter(|(tag, _)| !tags.contains(tag))
+            .map(|(tag, (_, arity))| Pattern::Constructor {
+                union: union.clone(),
+                tag,
+                args: vec![Pattern::Anything; *arity],
+            })
+            .collect::<Vec<Pattern>>();
+        missing(&default(matrix), width - 1)
+            .into_iter()
+            .flat_map(|row| {
+                absent
+                    .iter()
+                    .map(|first| [vec![first.clone()], row.clone()].concat())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+fn specialize(matrix: &[Vec<Pattern>], tag: usize, arity: usize) -> Vec<Vec<Pattern>> {
+    matrix
+        .iter()
+        .filter_map(|row| match row.split_first()? {
+            (Pattern::Constructor { tag: t, args, .. }, rest) if *t == tag => {
+                Some([args.as_slice(), rest].concat())
+            }
+            (Pattern::Constructor { .. }, _) => None,
+            (Pattern::Anything, rest) => {
+                Some([vec![Pattern::Anything; arity].as_slice(), rest].concat())
+            }
+        })
+        .collect()
+}
+
+fn default(matrix: &[Vec<Pattern>]) -> Vec<Vec<Pattern>> {
+    matrix
+        .iter()
+        .filter_map(|row| match row.split_first()? {
+            (Pattern::Anything, rest) => Some(rest.to_vec()),
+            (Pattern::Constructor { .. }, _) => None,
+        })
+        .collect()
+}
+
+fn first_tags(matrix: &[Vec<Pattern>]) -> HashSet<usize> {
+    matrix
+        .iter()
+        .filter_map(|row| match row.first()? {
+            Pattern::Constructor { tag, .. } => Some(*tag),
+            Pattern::Anything => None,
+        })
+        .collect()
+}
+
+fn first_union(matrix: &[Vec<Pattern>]) -> Option<Union> {
+    matrix.iter().find_map(|row| match row.first()? {
+        Pattern::Constructor { union, .. } => Some(union.clone()),
+        Pattern::Anything => None,
+    })
+}
+
+fn complete_union(matrix: &[Vec<Pattern>]) -> Option<Union> {
+    let union = first_union(matrix)?;
+    if first_tags(matrix).len() == union.alternatives.len() {
+        Some(union)
+    } else {
+        None
+    }
+}
+
+// EXAMPLES
+
+fn example(pattern: Pattern) -> source::Pattern {
+    let inner = match pattern {
+        Pattern::Anything => source::Pattern_::Wildcard,
+        Pattern::Constructor { union, tag, args } => {
+            let mut args = args.into_iter().map(example).collect::<Vec<_>>();
+            match union.kind {
+                UnionKind::Tuple => source::Pattern_::Tuple(args),
+                UnionKind::List if args.len() == 2 => {
+                    let list = args.pop().unwrap();
+                    let element = args.pop().unwrap();
+                    source::Pattern_::Cons(Box::new(element), Box::new(list))
+                }
+                UnionKind::List => source::Pattern_::EmptyList,
+                UnionKind::Custom => {
+                    source::Pattern_::Constructor(union.alternatives[tag].0.clone(), args)
+                }
+            }
+        }
+    };
+    Located {
+        region: Region::ZERO,
+        inner,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::pretty::PrettyPrint;
+
+    fn list() -> Union {
+        Union {
+            kind: UnionKind::List,
+            alternatives: vec![("Empty".to_owned(), 0), ("Cons".to_owned(), 2)],
+        }
+    }
+
+    fn maybe() -> Union {
+        Union {
+            kind: UnionKind::Custom,
+            alternatives: vec![("Nothing".to_owned(), 0), ("Just".to_owned(), 1)],
+        }
+    }
+
+    fn bool() -> Union {
+        Union {
+            kind: UnionKind::Custom,
+            alternatives: vec![("False".to_owned(), 0), ("True".to_owned(), 0)],
+        }
+    }
+
+    fn constructor(union: Union, tag: usize, args: Vec<Pattern>) -> Pattern {
+        Pattern::Constructor { union, tag, args }
+    }
+
+    fn empty() -> Pattern {
+        constructor(list(), 0, vec![])
+    }
+
+    fn cons(element: Pattern, rest: Pattern) -> Pattern {
+        constructor(list(), 1, vec![element, rest])
+    }
+
+    fn tuple(elements: Vec<Pattern>) -> Pattern {
+        let union = Union {
+            kind: UnionKind::Tuple,
+            alternatives: vec![("".to_owned(), elements.len())],
+        };
+        constructor(union, 0, elements)
+    }
+
+    fn missing_examples(rows: Vec<Pattern>) -> Vec<String> {
+        let matrix = rows.into_iter().map(|row| vec![row]).collect::<Vec<_>>();
+        missing(&matrix, 1)
+            .into_iter()
+            .map(|mut row| example(row.remove(0)).pretty_print())
+            .collect()
+    }
+
+    #[test]
+    fn empty_and_cons_cover_every_list() {
+        let matrix = vec![
+            vec![empty()],
+            vec![cons(Pattern::Anything, Pattern::Anything)],
+        ];
+        assert!(missing(&matrix, 1).is_empty());
+        assert!(!is_useful(&matrix, &[Pattern::Anything]));
+    }
+
+    #[test]
+    fn a_branch_after_a_wildcard_is_useless() {
+        let matrix = vec![vec![Pattern::Anything]];
+        assert!(!is_useful(&matrix, &[constructor(maybe(), 0, vec![])]));
+        assert!(is_useful(&[], &[Pattern::Anything]));
+    }
+
+    #[test]
+    fn missing_constructors_are_given_as_examples() {
+        assert_eq!(
+            missing_examples(vec![constructor(maybe(), 1, vec![Pattern::Anything])]),
+            vec!["Nothing"]
+        );
+    }
+
+    #[test]
+    fn longer_lists_are_missing_from_short_ones() {
+        assert_eq!(
+            missing_examples(vec![empty(), cons(Pattern::Anything, empty())]),
+            vec!["_ :: _ :: _"]
+        );
+    }
+
+    #[test]
+    fn tuples_are_missing_the_combinations_not_covered() {
+        let rows = vec![
+            tuple(vec![
+                constructor(maybe(), 1, vec![Pattern::Anything]),
+                Pattern::Anything,
+            ]),
+            tuple(vec![
+                constructor(maybe(), 0, vec![]),
+                constructor(bool(), 1, vec![]),
+            ]),
+        ];
+        assert_eq!(missing_examples(rows.clone()), vec!["(Nothing, False)"]);
+
+        let matrix = rows.into_iter().map(|row| vec![row]).collect::<Vec<_>>();
+        let covered = tuple(vec![
+            constructor(maybe(), 1, vec![Pattern::Anything]),
+            constructor(bool(), 0, vec![]),
+        ]);
+        assert!(!is_useful(&matrix, &[covered]));
+    }
+}
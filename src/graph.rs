@@ -0,0 +1,152 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    hash::Hash,
+};
+
+// groups the nodes of a graph into strongly connected components using Tarjan's algorithm. The
+// components come out in dependency order, so every edge points either into its own component or
+// into one that came before it. Edges to nodes which aren't in the graph are ignored.
+pub fn strongly_connected_components<K>(graph: &[(K, Vec<K>)]) -> Vec<Vec<K>>
+where
+    K: Clone + Eq + Hash,
+{
+    let indices = graph
+        .iter()
+        .enumerate()
+        .map(|(index, (node, _))| (node.clone(), index))
+        .collect::<HashMap<K, usize>>();
+    let edges = graph
+        .iter()
+        .map(|(_, edges)| {
+            edges
+                .iter()
+                .filter_map(|edge| indices.get(edge).copied())
+                .collect()
+        })
+        .collect::<Vec<Vec<usize>>>();
+
+    let mut tarjan = Tarjan {
+        edges: &edges,
+        next_index: 0,
+        index: vec![None; graph.len()],
+        low_link: vec![0; graph.len()],
+        stack: vec![],
+        on_stack: vec![false; graph.len()],
+        components: vec![],
+    };
+    for node in 0..graph.len() {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+
+    tarjan
+        .components
+        .into_iter()
+        .map(|component| {
+            component
+                .into_iter()
+                .map(|node| graph[node].0.clone())
+                .collect()
+        })
+        .collect()
+}
+
+// whether a component has to be treated as cyclic, which is the case when it has more than one
+// node or a single node with an edge to itself.
+pub fn is_cyclic<K>(graph: &[(K, Vec<K>)], component: &[K]) -> bool
+where
+    K: Eq,
+{
+    match component {
+        [node] => graph
+            .iter()
+            .any(|(other, edges)| other == node && edges.contains(node)),
+        _ => true,
+    }
+}
+
+// the shortest path from a node back to itself, starting and ending with that node.
+pub fn cycle<K>(graph: &[(K, Vec<K>)], start: &K) -> Option<Vec<K>>
+where
+    K: Clone + Eq + Hash,
+{
+    let edges = graph
+        .iter()
+        .map(|(node, edges)| (node, edges))
+        .collect::<HashMap<&K, &Vec<K>>>();
+
+    let mut previous: HashMap<K, K> = HashMap::new();
+    let mut queue = VecDeque::from([start.clone()]);
+    while let Some(node) = queue.pop_front() {
+        for next in edges
+            .get(&node)
+            .map(|edges| edges.iter())
+            .into_iter()
+            .flatten()
+        {
+            if next == start {
+                let mut path = vec![node.clone()];
+                let mut current = node;
+                while current != *start {
+                    current = previous[&current].clone();
+                    path.push(current.clone());
+                }
+                path.reverse();
+                path.push(start.clone());
+                return Some(path);
+            }
+            if !previous.contains_key(next) && edges.contains_key(next) {
+                previous.insert(next.clone(), node.clone());
+                queue.push_back(next.clone());
+            }
+        }
+    }
+    None
+}
+
+struct Tarjan<'a> {
+    edges: &'a [Vec<usize>],
+    next_index: usize,
+    index: Vec<Option<usize>>,
+    low_link: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    components: Vec<Vec<usize>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, node: usize) {
+        self.index[node] = Some(self.next_index);
+        self.low_link[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        for &next in self.edges[node].iter() {
+            match self.index[next] {
+                None => {
+                    self.visit(next);
+                    self.low_link[node] = self.low_link[node].min(self.low_link[next]);
+                }
+                Some(index) if self.on_stack[next] => {
+                    self.low_link[node] = self.low_link[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low_link[node]) == self.index[node] {
+            let mut component = vec![];
+            while let Some(other) = self.stack.pop() {
+                self.on_stack[other] = false;
+                component.push(other);
+                if other == node {
+                    break;
+                }
+            }
+            component.reverse();
+            self.components.push(component);
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::{collections::HashMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::ast::{
+    canonical::{self, Alias, Annotation, Constructor, Export, External, Union},
+    ModuleName, Name,
+};
+
+const CACHE_DIR: &str = "build/cache/";
+
+// everything other modules need to know about a module once it has compiled. Keeping this around
+// is what lets a module be skipped until it or something it imports changes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Interface {
+    pub key: u64, // a hash of the module's source along with the keys of everything it imports
+    pub imports: Vec<ModuleName>,
+    pub exports: Vec<Export>,
+    pub unions: HashMap<Name, Union>,
+    pub aliases: HashMap<Name, Alias>,
+    pub external_types: HashMap<Name, External>,
+    pub constructors: HashMap<Name, Constructor>,
+    pub values: HashMap<Name, Annotation>,
+}
+
+impl Interface {
+    // `values` are the types the type checker found for the module's definitions, since some of
+    // them may not have been annotated.
+    pub fn new(key: u64, module: &canonical::Module, values: HashMap<Name, Annotation>) -> Self {
+        Self {
+            key,
+            imports: module.imports.clone(),
+            exports: module.exports.clone(),
+            unions: module.unions.clone(),
+            aliases: module.aliases.clone(),
+            external_types: module.external_types.clone(),
+            constructors: module.constructors.clone(),
+            values,
+        }
+    }
+
+    // stands in for the module in the passes after canonicalization, which only need to look at
+    // its types since its definitions have already been checked and compiled.
+    pub fn module(&self) -> canonical::Module {
+        canonical::Module {
+            unions: self.unions.clone(),
+            aliases: self.aliases.clone(),
+            external_types: self.external_types.clone(),
+            constructors: self.constructors.clone(),
+            definitions: vec![],
+            imports: self.imports.clone(),
+            exports: self.exports.clone(),
+        }
+    }
+}
+
+// keys are saved between runs, so they are hashed with FNV-1a, which unlike the standard library's
+// hasher gives the same result on every Rust release. A different compiler may compile the same
+// source differently, so its version is part of the key as well.
+pub fn key(source: &str, imports: &[u64]) -> u64 {
+    let mut bytes = env!("CARGO_PKG_VERSION").as_bytes().to_vec();
+    bytes.push(0);
+    bytes.extend(source.as_bytes());
+    bytes.push(0);
+    for import in imports.iter() {
+        bytes.extend(import.to_le_bytes());
+    }
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+// the cached interfaces which are still up to date, which is when neither the module's source nor
+// anything it imports has changed since it was last compiled, and its compiled output is still
+// there.
+pub fn up_to_date<F>(
+    sources: &HashMap<ModuleName, &str>,
+    compiled: F,
+) -> HashMap<ModuleName, Interface>
+where
+    F: Fn(&ModuleName) -> bool,
+{
+    fn visit<F>(
+        module_name: &ModuleName,
+        sources: &HashMap<ModuleName, &str>,
+        compiled: &F,
+        visited: &mut HashMap<ModuleName, Option<Interface>>,
+    ) -> Option<u64>
+    where
+        F: Fn(&ModuleName) -> bool,
+    {
+        if let Some(interface) = visited.get(module_name) {
+            return interface.as_ref().map(|interface| interface.key);
+        }
+        // an import cycle can't have been compiled, so anything on one is out of date.
+        visited.insert(module_name.clone(), None);
+
+        let source = sources.get(module_name)?;
+        let interface = load(module_name).filter(|_| compiled(module_name))?;
+        let imports = interface
+            .imports
+            .iter()
+            .map(|import| visit(import, sources, compiled, visited))
+            .collect::<Option<Vec<u64>>>()?;
+        if key(source, &imports) != interface.key {
+            return None;
+        }
+
+        visited.insert(module_name.clone(), Some(interface));
+        visited[module_name].as_ref().map(|interface| interface.key)
+    }
+
+    let mut visited = HashMap::new();
+    for module_name in sources.keys() {
+        visit(module_name, sources, &compiled, &mut visited);
+    }
+    visited
+        .into_iter()
+        .filter_map(|(module_name, interface)| Some((module_name, interface?)))
+        .collect()
+}
+
+// the key of every module, which depends on the keys of its imports, so it has to be worked out
+// after them.
+pub fn keys(
+    sources: &HashMap<ModuleName, &str>,
+    modules: &HashMap<ModuleName, canonical::Module>,
+) -> HashMap<ModuleName, u64> {
+    fn visit(
+        module_name: &ModuleName,
+        sources: &HashMap<ModuleName, &str>,
+        modules: &HashMap<ModuleName, canonical::Module>,
+        keys: &mut HashMap<ModuleName, u64>,
+    ) -> u64 {
+        if let Some(key) = keys.get(module_name) {
+            return *key;
+        }
+        let imports = modules[module_name]
+            .imports
+            .iter()
+            .map(|import| visit(import, sources, modules, keys))
+            .collect::<Vec<u64>>();
+        let module_key = key(sources[module_name], &imports);
+        keys.insert(module_name.clone(), module_key);
+        module_key
+    }
+
+    let mut keys = HashMap::new();
+    for module_name in modules.keys() {
+        visit(module_name, sources, modules, &mut keys);
+    }
+    keys
+}
+
+fn path(module_name: &ModuleName) -> PathBuf {
+    PathBuf::from(format!("{}{}.json", CACHE_DIR, module_name))
+}
+
+fn load(module_name: &ModuleName) -> Option<Interface> {
+    let json = std::fs::read_to_string(path(module_name)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+pub fn save(module_name: &ModuleName, interface: &Interface) -> std::io::Result<()> {
+    std::fs::create_dir_all(CACHE_DIR)?;
+    let json = serde_json::to_string(interface)?;
+    std::fs::write(path(module_name), json)
+}
@@ -3,13 +3,15 @@
 mod ast;
 mod canonicalize;
 mod compile;
+mod exhaustiveness;
+mod graph;
+mod interface;
 mod optimize;
 mod parse;
 mod report;
 mod type_check;
 mod util;
 
-use nom::{combinator::complete, Finish, Parser};
 use std::{
     collections::HashMap,
     fs,
@@ -17,8 +19,9 @@ use std::{
 };
 
 use crate::{
-    ast::{ModuleName, Name, Span},
-    canonicalize::canonicalize,
+    ast::{ModuleName, Name},
+    canonicalize::{canonicalize, check_imports},
+    interface::Interface,
     optimize::optimize,
     report::{code::Source, pretty::PrettyPrint, Report},
     type_check::type_check,
@@ -32,7 +35,9 @@ fn get_paths(root_dir: &Path) -> Vec<PathBuf> {
         let entry = entry.unwrap();
         let path = entry.path();
         if path.is_file() {
-            paths.push(path);
+            if path.extension().is_some_and(|extension| extension == "pac") {
+                paths.push(path);
+            }
         } else {
             let sub_paths = get_paths(&path);
             paths.extend(sub_paths.into_iter())
@@ -41,62 +46,246 @@ fn get_paths(root_dir: &Path) -> Vec<PathBuf> {
     return paths;
 }
 
-fn load_module(path: PathBuf) -> Option<ast::source::Module> {
-    let file_str = std::fs::read_to_string(&path).unwrap();
-    let file = Span::new(&file_str);
-    let file_source = Source::new(&file_str);
-    let file_name = path.as_os_str().to_str().unwrap();
+// the name a module has to have given where its file is, so `Game/Physics.pac` is `Game.Physics`.
+fn module_name(root_dir: &Path, path: &Path) -> ModuleName {
+    ModuleName(
+        path.strip_prefix(root_dir)
+            .unwrap()
+            .with_extension("")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .collect(),
+    )
+}
 
-    let parse_results = complete(parse::file).parse(file).finish();
+fn width() -> u32 {
+    termsize::get()
+        .map(|size| std::cmp::min(size.cols as u32, 80))
+        .unwrap_or(80)
+}
 
-    match parse_results {
-        Ok((_, module)) => Some(module),
-        Err(err) => {
-            eprintln!(
-                "\n{}",
-                err.to_report(file_source, file_name)
-                    .render(std::cmp::min(termsize::get().unwrap().cols as u32, 80))
-            );
+// finds every module in a folder, named after where its file is.
+fn read(root_dir: &Path) -> Vec<(ModuleName, File)> {
+    get_paths(root_dir)
+        .into_iter()
+        .map(|path| {
+            (
+                module_name(root_dir, &path),
+                File {
+                    path: path.as_os_str().to_str().unwrap().to_owned(),
+                    code: std::fs::read_to_string(&path).unwrap(),
+                },
+            )
+        })
+        .collect()
+}
+
+fn load_module(expected: &ModuleName, file: &File) -> Option<ast::source::Module> {
+    let file_source = Source::new(&file.code);
+    match parse::module(&file.code) {
+        Ok(module) => {
+            if module.name.inner != *expected {
+                let error = report::error::module::Error::NameMismatch {
+                    region: module.name.region.clone(),
+                    expected: expected.clone(),
+                    found: module.name.inner.clone(),
+                };
+                eprintln!(
+                    "\n{}",
+                    error.to_report(file_source, &file.path).render(width())
+                );
+                return None;
+            }
+            if !module.duplicates.is_empty() {
+                for duplicate in module.duplicates.iter() {
+                    let error = report::error::naming::Error::Duplicate {
+                        declaration: duplicate.declaration,
+                        name: duplicate.name.clone(),
+                        first: duplicate.first.clone(),
+                        second: duplicate.second.clone(),
+                    };
+                    eprintln!(
+                        "\n{}",
+                        error
+                            .to_report(Source::new(&file.code), &file.path)
+                            .render(width())
+                    );
+                }
+                return None;
+            }
+            Some(module)
+        }
+        Err(errors) => {
+            for error in errors {
+                eprintln!(
+                    "\n{}",
+                    error
+                        .to_report(Source::new(&file.code), &file.path)
+                        .render(width())
+                );
+            }
             None
         }
     }
 }
 
-fn load(root_dir: &Path) -> Option<Vec<ast::source::Module>> {
-    let paths = get_paths(root_dir);
-    let num_paths = paths.len();
-    let modules: Vec<ast::source::Module> = paths.into_iter().filter_map(load_module).collect();
-    if modules.len() < num_paths {
+// parses the modules which can't be skipped because they, or something they import, changed.
+fn load(
+    files: &[(ModuleName, File)],
+    interfaces: &HashMap<ModuleName, Interface>,
+) -> Option<Vec<ast::source::Module>> {
+    let stale = files
+        .iter()
+        .filter(|(module_name, _)| !interfaces.contains_key(module_name))
+        .collect::<Vec<_>>();
+    let modules: Vec<ast::source::Module> = stale
+        .iter()
+        .filter_map(|(module_name, file)| load_module(module_name, file))
+        .collect();
+    if modules.len() < stale.len() {
         None
     } else {
         Some(modules)
     }
 }
 
+fn report(
+    files: &HashMap<ModuleName, File>,
+    module_name: &ModuleName,
+    error: report::error::Error,
+) {
+    let file = &files[module_name];
+    eprintln!(
+        "\n{}",
+        error
+            .to_report(Source::new(&file.code), &file.path)
+            .render(width())
+    );
+}
+
+// the source code of each module is kept around so that errors found after parsing can still
+// show the offending code.
+struct File {
+    path: String,
+    code: String,
+}
+
 fn main() {
     //std::env::set_var("RUST_BACKTRACE", "1");
     let args: Vec<String> = std::env::args().collect();
     let root_dir = Path::new("test"); //(&args[1]);
 
+    let builtin_files = read(Path::new("src/basics"));
+    let user_files = read(root_dir);
+
+    // TODO: import all builtin modules to the user modules
+    let mut files: HashMap<ModuleName, File> = HashMap::new();
+    let mut duplicates = false;
+    for (module_name, file) in builtin_files.iter().chain(user_files.iter()) {
+        if let Some(other) = files.get(module_name) {
+            if let Some(module) = load_module(module_name, file) {
+                let error = report::error::module::Error::Duplicate {
+                    region: module.name.region.clone(),
+                    module: module_name.clone(),
+                    other: other.path.clone(),
+                };
+                eprintln!(
+                    "\n{}",
+                    error
+                        .to_report(Source::new(&file.code), &file.path)
+                        .render(width())
+                );
+            }
+            duplicates = true;
+            continue;
+        }
+        files.insert(
+            module_name.clone(),
+            File {
+                path: file.path.clone(),
+                code: file.code.clone(),
+            },
+        );
+    }
+    if duplicates {
+        std::process::exit(1);
+    }
+
+    let sources = files
+        .iter()
+        .map(|(module_name, file)| (module_name.clone(), file.code.as_str()))
+        .collect::<HashMap<ModuleName, &str>>();
+    let interfaces = interface::up_to_date(&sources, compile::is_compiled);
+
     let mut modules = vec![];
-    let Some(mut builtin_modules) = load(Path::new("src/basics")) else {
+    let Some(mut builtin_modules) = load(&builtin_files, &interfaces) else {
         eprintln!("\n\nI couldn't load all of the built in modules!");
         std::process::exit(1);
     };
     modules.append(&mut builtin_modules);
 
-    let Some(mut user_modules) = load(root_dir) else {
+    let Some(mut user_modules) = load(&user_files, &interfaces) else {
         eprintln!(
             "\n\nI couldn't load all the modules in the {:?} folder!",
             root_dir
         );
         std::process::exit(1);
     };
-    // TODO: import all builtin modules to the user modules
     modules.append(&mut user_modules);
 
-    let modules = canonicalize(modules);
-    type_check(&modules).expect("failed type checking");
-    let modules = optimize(&modules);
-    compile::compile(modules);
+    if let Err(errors) = check_imports(&modules, &interfaces) {
+        for error in errors {
+            let report = error.to_report(|module_name| {
+                let file = &files[module_name];
+                (file.path.as_str(), Source::new(&file.code))
+            });
+            eprintln!("\n{}", report.render(width()));
+        }
+        std::process::exit(1);
+    }
+    let modules = match canonicalize(modules, &interfaces) {
+        Ok(modules) => modules,
+        Err(errors) => {
+            for (module_name, error) in errors {
+                report(&files, &module_name, error);
+            }
+            std::process::exit(1);
+        }
+    };
+    let mut types = match type_check(&modules, &interfaces) {
+        Ok(types) => types,
+        Err((module_name, error)) => {
+            report(&files, &module_name, report::error::Error::Type(error));
+            std::process::exit(1);
+        }
+    };
+    if let Err(errors) = exhaustiveness::check(&modules) {
+        for (module_name, error) in errors {
+            report(&files, &module_name, report::error::Error::Pattern(error));
+        }
+        std::process::exit(1);
+    }
+
+    let keys = interface::keys(&sources, &modules);
+    let optimized = optimize(&modules)
+        .into_iter()
+        .filter(|(module_name, _)| !interfaces.contains_key(module_name))
+        .collect();
+    if let Err(error) = compile::compile(optimized) {
+        eprintln!("\n\nI couldn't write the compiled modules: {}", error);
+        std::process::exit(1);
+    }
+    for (module_name, module) in modules.iter() {
+        if interfaces.contains_key(module_name) {
+            continue;
+        }
+        let values = types.remove(module_name).unwrap_or_default();
+        let interface = Interface::new(keys[module_name], module, values);
+        if let Err(error) = interface::save(module_name, &interface) {
+            eprintln!(
+                "\n\nI couldn't cache the interface of {}: {}",
+                module_name, error
+            );
+        }
+    }
 }
@@ -0,0 +1,324 @@
+use crate::ast::{
+    canonical::{Constructor, Pattern, Pattern_},
+    optimized::{DecisionTree, Path, Test},
+    Name, Qualified,
+};
+
+// what the tree needs to know about the union a constructor belongs to.
+pub struct Layout {
+    pub alternatives: usize,
+    // `Bool` is JS's own `true` and `false` at runtime rather than a tagged object, so it's tested
+    // differently.
+    pub is_bool: bool,
+}
+
+// turns the patterns of a `when` expression into a tree of tests, where each leaf is the index
+// of the branch to take. Every test is only ever performed once on any route through the tree.
+
+struct Branch {
+    goal: usize,
+    patterns: Vec<(Path, Pattern)>,
+}
+
+pub fn compile<F>(patterns: &[&Pattern], layout: &F) -> DecisionTree<usize>
+where
+    F: Fn(&Qualified<Constructor>) -> Layout,
+{
+    let branches = patterns
+        .iter()
+        .enumerate()
+        .map(|(goal, pattern)| Branch {
+            goal,
+            patterns: vec![(Path::Root, (*pattern).clone())],
+        })
+        .collect();
+    to_tree(branches, layout)
+}
+
+pub fn bindings(pattern: &Pattern) -> Vec<(Name, Path)> {
+    let mut bindings = vec![];
+    collect_bindings(pattern, Path::Root, &mut bindings);
+    bindings
+}
+
+fn collect_bindings(pattern: &Pattern, path: Path, bindings: &mut Vec<(Name, Path)>) {
+    match &pattern.inner {
+        Pattern_::Wildcard => {}
+        Pattern_::Identifier(name) => bindings.push((name.clone(), path)),
+        Pattern_::Constructor(_, args) | Pattern_::Tuple(args) => {
+            for (i, arg) in args.iter().enumerate() {
+                collect_bindings(arg, Path::Index(i as u16, Box::new(path.clone())), bindings);
+            }
+        }
+    }
+}
+
+fn to_tree<F>(branches: Vec<Branch>, layout: &F) -> DecisionTree<usize>
+where
+    F: Fn(&Qualified<Constructor>) -> Layout,
+{
+    let mut branches = branches.into_iter().map(flatten).collect::<Vec<Branch>>();
+
+    // exhaustiveness checking guarantees that some branch always matches.
+    let first = &branches[0];
+    let Some((path, _)) = first.patterns.first() else {
+        return DecisionTree::Succeed(first.goal);
+    };
+    let path = path.clone();
+
+    let mut tests: Vec<(u16, usize)> = vec![];
+    let mut complete = false;
+    let mut is_bool = false;
+    for branch in branches.iter() {
+        if let Some(Pattern_::Constructor(constructor, args)) = pattern_at(branch, &path) {
+            let tag = constructor.get().tag;
+            if !tests.iter().any(|(t, _)| *t == tag) {
+                tests.push((tag, args.len()));
+            }
+            let union = layout(constructor);
+            complete = tests.len() == union.alternatives;
+            is_bool = union.is_bool;
+        }
+    }
+
+    // a union with a single variant always matches, so there's nothing to test.
+    if complete && tests.len() == 1 {
+        let (tag, arity) = tests[0];
+        let specialized = branches
+            .iter()
+            .filter_map(|branch| specialize(branch, &path, tag, arity))
+            .collect();
+        return to_tree(specialized, layout);
+    }
+
+    let edges = tests
+        .iter()
+        .map(|(tag, arity)| {
+            let specialized = branches
+                .iter()
+                .filter_map(|branch| specialize(branch, &path, *tag, *arity))
+                .collect();
+            let test = if is_bool {
+                // `True` is declared first, so it has the tag 0.
+                Test::IsBool(*tag == 0)
+            } else {
+                Test::IsConstructor(*tag)
+            };
+            (test, to_tree(specialized, layout))
+        })
+        .collect();
+
+    branches.retain(|branch| pattern_at(branch, &path).is_none());
+    let fallback = if complete || branches.is_empty() {
+        None
+    } else {
+        Some(Box::new(to_tree(branches, layout)))
+    };
+
+    DecisionTree::Switch {
+        path,
+        edges,
+        fallback,
+    }
+}
+
+// drops every pattern that can't fail, and splits tuples into their elements since they only
+// have a single shape.
+fn flatten(branch: Branch) -> Branch {
+    let mut patterns = vec![];
+    let mut stack = branch.patterns;
+    stack.reverse();
+    while let Some((path, pattern)) = stack.pop() {
+        match pattern.inner {
+            Pattern_::Wildcard | Pattern_::Identifier(_) => {}
+            Pattern_::Tuple(elements) => {
+                for (i, element) in elements.into_iter().enumerate().rev() {
+                    stack.push((Path::Index(i as u16, Box::new(path.clone())), element));
+                }
+            }
+            Pattern_::Constructor(_, _) => patterns.push((path, pattern)),
+        }
+    }
+    Branch {
+        goal: branch.goal,
+        patterns,
+    }
+}
+
+fn pattern_at<'a>(branch: &'a Branch, path: &Path) -> Option<&'a Pattern_> {
+    branch
+        .patterns
+        .iter()
+        .find(|(p, _)| p == path)
+        .map(|(_, pattern)| &pattern.inner)
+}
+
+// keeps the branches which are still possible once the value at `path` is known to have been
+// built with the constructor `tag`, replacing that test with tests on its arguments.
+fn specialize(branch: &Branch, path: &Path, tag: u16, arity: usize) -> Option<Branch> {
+    let mut patterns = vec![];
+    for (p, pattern) in branch.patterns.iter() {
+        if p != path {
+            patterns.push((p.clone(), pattern.clone()));
+            continue;
+        }
+        match &pattern.inner {
+            Pattern_::Constructor(constructor, args) if constructor.get().tag == tag => {
+                debug_assert_eq!(args.len(), arity);
+                for (i, arg) in args.iter().enumerate() {
+                    patterns.push((Path::Index(i as u16, Box::new(p.clone())), arg.clone()));
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(Branch {
+        goal: branch.goal,
+        patterns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::ast::{
+        canonical::{Annotation, Type},
+        Located, Region,
+    };
+
+    // a value being matched on, which is a tag along with the values it was built from.
+    struct Value(u16, Vec<Value>);
+
+    fn located(inner: Pattern_) -> Pattern {
+        Located {
+            region: Region::ZERO,
+            inner,
+        }
+    }
+
+    fn constructor(tag: u16, args: Vec<Pattern>) -> Pattern {
+        let constructor = Constructor {
+            tag,
+            arity: args.len() as u16,
+            annotation: Annotation {
+                quantified: HashSet::new(),
+                tipe: Type::Unit,
+            },
+        };
+        located(Pattern_::Constructor(Qualified::Local(constructor), args))
+    }
+
+    fn empty() -> Pattern {
+        constructor(0, vec![])
+    }
+
+    fn cons(element: Pattern, rest: Pattern) -> Pattern {
+        constructor(1, vec![element, rest])
+    }
+
+    fn variable(name: &str) -> Pattern {
+        located(Pattern_::Identifier(name.to_owned()))
+    }
+
+    fn wildcard() -> Pattern {
+        located(Pattern_::Wildcard)
+    }
+
+    fn list(length: usize) -> Value {
+        (0..length).fold(Value(0, vec![]), |rest, _| {
+            Value(1, vec![Value(0, vec![]), rest])
+        })
+    }
+
+    fn at<'a>(value: &'a Value, path: &Path) -> &'a Value {
+        match path {
+            Path::Root => value,
+            Path::Index(i, parent) => &at(value, parent).1[*i as usize],
+        }
+    }
+
+    fn run(tree: &DecisionTree<usize>, value: &Value) -> usize {
+        match tree {
+            DecisionTree::Succeed(goal) => *goal,
+            DecisionTree::Switch {
+                path,
+                edges,
+                fallback,
+            } => {
+                let tag = at(value, path).0;
+                let next = edges
+                    .iter()
+                    .find(|(test, _)| matches!(test, Test::IsConstructor(t) if *t == tag))
+                    .map(|(_, tree)| tree)
+                    .or(fallback.as_deref())
+                    .expect("every value is matched by some branch");
+                run(next, value)
+            }
+        }
+    }
+
+    fn tree(patterns: &[Pattern]) -> DecisionTree<usize> {
+        compile(&patterns.iter().collect::<Vec<_>>(), &|_| Layout {
+            alternatives: 2,
+            is_bool: false,
+        })
+    }
+
+    #[test]
+    fn nested_cons_patterns_match_by_length() {
+        let tree = tree(&[
+            empty(),
+            cons(wildcard(), empty()),
+            cons(wildcard(), cons(wildcard(), wildcard())),
+        ]);
+        assert_eq!(run(&tree, &list(0)), 0);
+        assert_eq!(run(&tree, &list(1)), 1);
+        assert_eq!(run(&tree, &list(2)), 2);
+        assert_eq!(run(&tree, &list(5)), 2);
+    }
+
+    #[test]
+    fn earlier_branches_win() {
+        let tree = tree(&[cons(wildcard(), cons(wildcard(), empty())), wildcard()]);
+        assert_eq!(run(&tree, &list(2)), 0);
+        assert_eq!(run(&tree, &list(1)), 1);
+        assert_eq!(run(&tree, &list(3)), 1);
+    }
+
+    #[test]
+    fn tuple_elements_are_tested_separately() {
+        let pair = |first, second| located(Pattern_::Tuple(vec![first, second]));
+        let tree = tree(&[
+            pair(empty(), wildcard()),
+            pair(wildcard(), empty()),
+            pair(cons(wildcard(), wildcard()), cons(wildcard(), wildcard())),
+        ]);
+        let value = |first, second| Value(0, vec![list(first), list(second)]);
+        assert_eq!(run(&tree, &value(0, 0)), 0);
+        assert_eq!(run(&tree, &value(0, 3)), 0);
+        assert_eq!(run(&tree, &value(2, 0)), 1);
+        assert_eq!(run(&tree, &value(1, 1)), 2);
+    }
+
+    #[test]
+    fn bindings_follow_the_path_into_nested_patterns() {
+        let pattern = cons(variable("x"), cons(variable("y"), variable("rest")));
+        let root = || Box::new(Path::Root);
+        assert_eq!(
+            bindings(&pattern),
+            vec![
+                ("x".to_owned(), Path::Index(0, root())),
+                (
+                    "y".to_owned(),
+                    Path::Index(0, Box::new(Path::Index(1, root())))
+                ),
+                (
+                    "rest".to_owned(),
+                    Path::Index(1, Box::new(Path::Index(1, root())))
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,220 @@
+mod decision_tree;
+
+use std::collections::HashMap;
+
+use crate::ast::{canonical, optimized, ModuleName, Name, Qualified};
+
+use decision_tree::Layout;
+
+pub fn optimize(
+    modules: &HashMap<ModuleName, canonical::Module>,
+) -> HashMap<ModuleName, optimized::Module> {
+    let mut optimized = HashMap::new();
+    for (name, module) in modules {
+        let layout = |constructor: &Qualified<canonical::Constructor>| {
+            let home = match constructor {
+                Qualified::Foreign { module, .. } => module,
+                Qualified::Local(_) | Qualified::Kernel(_) => name,
+            };
+            let (home, union) = match constructor.get().union() {
+                Qualified::Foreign { module, member } => (module, member),
+                Qualified::Local(union) | Qualified::Kernel(union) => (home, union),
+            };
+            Layout {
+                alternatives: modules[home].unions[union].variants.len(),
+                is_bool: *home == ModuleName(vec!["Bool".to_owned()]) && union == "Bool",
+            }
+        };
+        optimized.insert(
+            name.clone(),
+            optimized::Module {
+                imports: module.imports.clone(),
+                exports: module
+                    .exports
+                    .iter()
+                    .map(|export| {
+                        (match export {
+                            canonical::Export::Value(name) => name,
+                            canonical::Export::ClosedType(name) => name,
+                            canonical::Export::OpenType(name) => name,
+                        })
+                        .clone()
+                    })
+                    .collect(),
+                // the definitions are already in dependency order, which is the order JS needs
+                // them in too.
+                definitions: module
+                    .definitions
+                    .iter()
+                    .flat_map(|definitions| match definitions {
+                        canonical::Definitions::Recursive(definitions) => definitions.as_slice(),
+                        canonical::Definitions::NonRecursive(definition) => {
+                            std::slice::from_ref(definition)
+                        }
+                    })
+                    .map(|definition| {
+                        (
+                            definition.name.clone(),
+                            expression(definition.expr.clone(), &layout),
+                        )
+                    })
+                    .collect(),
+            },
+        );
+    }
+    optimized
+}
+
+fn expression<F>(canonical_expr: canonical::Expr, layout: &F) -> optimized::Expr
+where
+    F: Fn(&Qualified<canonical::Constructor>) -> Layout,
+{
+    match canonical_expr.inner {
+        canonical::Expr_::Variable(Qualified::Kernel(name)) => optimized::Expr::Extern(name),
+        canonical::Expr_::Variable(qualified) => optimized::Expr::Identifier(qualified),
+        // `True` and `False` are JS's own booleans, so that they're the same values that
+        // comparisons give.
+        canonical::Expr_::Constructor(qualified) if layout(&qualified).is_bool => {
+            optimized::Expr::Bool(qualified.get().tag == 0)
+        }
+        canonical::Expr_::Constructor(qualified) => {
+            let constructor = qualified.get();
+            optimized::Expr::Constructor {
+                tag: constructor.tag,
+                arity: constructor.arity,
+            }
+        }
+        canonical::Expr_::Unit => optimized::Expr::Constructor { tag: 0, arity: 0 },
+        canonical::Expr_::Bool(bool) => optimized::Expr::Bool(bool),
+        canonical::Expr_::Int(int) => optimized::Expr::Int(int),
+        canonical::Expr_::Float(float) => optimized::Expr::Float(float),
+        canonical::Expr_::String(string) => optimized::Expr::String(string),
+        canonical::Expr_::List(list) => optimized::Expr::List(
+            list.into_iter()
+                .map(|element| expression(element, layout))
+                .collect(),
+        ),
+        canonical::Expr_::Tuple(elements) => optimized::Expr::Tuple(
+            elements
+                .into_iter()
+                .map(|element| expression(element, layout))
+                .collect(),
+        ),
+        canonical::Expr_::Ap { function, arg } => optimized::Expr::Ap {
+            function: Box::new(expression(*function, layout)),
+            arg: Box::new(expression(*arg, layout)),
+        },
+        canonical::Expr_::Op { op, lhs, rhs } => optimized::Expr::Op {
+            op: operator(op),
+            lhs: Box::new(expression(*lhs, layout)),
+            rhs: Box::new(expression(*rhs, layout)),
+        },
+        canonical::Expr_::Let { name, expr, body } => optimized::Expr::Let {
+            name,
+            expr: Box::new(expression(*expr, layout)),
+            body: Box::new(expression(*body, layout)),
+        },
+        // a destructuring pattern is just a `when` expression with one branch.
+        canonical::Expr_::Destruct {
+            pattern,
+            expr,
+            body,
+        } => optimized::Expr::When {
+            expr: Box::new(expression(*expr, layout)),
+            decision_tree: Box::new(decision_tree::compile(&[&pattern], layout)),
+            branches: vec![optimized::Branch {
+                bindings: decision_tree::bindings(&pattern),
+                body: expression(*body, layout),
+            }],
+        },
+        canonical::Expr_::LetRec { defs, body } => optimized::Expr::LetRec {
+            defs: defs
+                .into_iter()
+                .map(|(name, def)| (name, expression(def, layout)))
+                .collect(),
+            body: Box::new(expression(*body, layout)),
+        },
+        canonical::Expr_::Lambda { arg, body } => optimized::Expr::Lambda {
+            arg,
+            body: Box::new(expression(*body, layout)),
+        },
+        canonical::Expr_::If {
+            cond,
+            true_branch,
+            false_branch,
+        } => optimized::Expr::If {
+            cond: Box::new(expression(*cond, layout)),
+            true_branch: Box::new(expression(*true_branch, layout)),
+            false_branch: Box::new(expression(*false_branch, layout)),
+        },
+        canonical::Expr_::When {
+            expr,
+            first_alternative,
+            rest_alternatives,
+        } => {
+            let when_branches = std::iter::once(*first_alternative)
+                .chain(rest_alternatives)
+                .collect::<Vec<(canonical::Pattern, canonical::Expr)>>();
+            let patterns = when_branches
+                .iter()
+                .map(|(pattern, _)| pattern)
+                .collect::<Vec<&canonical::Pattern>>();
+            optimized::Expr::When {
+                expr: Box::new(expression(*expr, layout)),
+                decision_tree: Box::new(decision_tree::compile(&patterns, layout)),
+                branches: when_branches
+                    .iter()
+                    .map(|(pattern, body)| optimized::Branch {
+                        bindings: decision_tree::bindings(pattern),
+                        body: expression(body.clone(), layout),
+                    })
+                    .collect(),
+            }
+        }
+        canonical::Expr_::Record(fields) => optimized::Expr::Record(record_fields(fields, layout)),
+        canonical::Expr_::Access { record, field } => optimized::Expr::Access {
+            record: Box::new(expression(*record, layout)),
+            field,
+        },
+        canonical::Expr_::Update { record, fields } => optimized::Expr::Update {
+            record: Box::new(expression(*record, layout)),
+            fields: record_fields(fields, layout),
+        },
+    }
+}
+
+// fields are sorted so that the generated code doesn't depend on the order of a `HashMap`.
+fn record_fields<F>(
+    fields: HashMap<Name, canonical::Expr>,
+    layout: &F,
+) -> Vec<(Name, optimized::Expr)>
+where
+    F: Fn(&Qualified<canonical::Constructor>) -> Layout,
+{
+    let mut fields = fields
+        .into_iter()
+        .map(|(field, expr)| (field, expression(expr, layout)))
+        .collect::<Vec<(Name, optimized::Expr)>>();
+    fields.sort_by(|(a, _), (b, _)| a.cmp(b));
+    fields
+}
+
+fn operator(op: canonical::Operator) -> optimized::Operator {
+    match op {
+        canonical::Operator::Or => optimized::Operator::Or,
+        canonical::Operator::And => optimized::Operator::And,
+        canonical::Operator::Eq => optimized::Operator::Eq,
+        canonical::Operator::Neq => optimized::Operator::Neq,
+        canonical::Operator::LT => optimized::Operator::LT,
+        canonical::Operator::LTE => optimized::Operator::LTE,
+        canonical::Operator::GT => optimized::Operator::GT,
+        canonical::Operator::GTE => optimized::Operator::GTE,
+        canonical::Operator::Concat => optimized::Operator::Plus,
+        canonical::Operator::Plus => optimized::Operator::Plus,
+        canonical::Operator::Minus => optimized::Operator::Minus,
+        canonical::Operator::Times => optimized::Operator::Times,
+        canonical::Operator::Divide => optimized::Operator::Divide,
+        canonical::Operator::Mod => optimized::Operator::Mod,
+        canonical::Operator::Power => optimized::Operator::Power,
+    }
+}
@@ -6,75 +6,91 @@ use crate::ast::source::{Assoc, Expr, Expr_, Operator, Pattern, Pattern_};
 
 use super::*;
 
-fn identifier(i: Span) -> Result<Expr> {
+fn identifier(i: Tokens) -> Result<Expr> {
     located(value_identifier.map(|s| Expr_::Identifier(s))).parse(i)
 }
 
-fn constructor(i: Span) -> Result<Expr> {
+fn constructor(i: Tokens) -> Result<Expr> {
     located(type_identifier.map(|s| Expr_::Constructor(s))).parse(i)
 }
 
-fn int(i: Span) -> Result<i32> {
-    lexeme(terminated(
-        tuple((
-            alt((tag("-").map(|_| -1), tag("+").map(|_| 1), success(1))),
-            digit1.map(|string: Span| string.fragment().parse::<i32>().unwrap()),
-        )),
-        not(tag(".")),
-    ))
-    .map(|(sign, num)| sign * num)
+fn int(i: Tokens) -> Result<i32> {
+    next(|token| match token {
+        Token::Int(int) => Some(*int),
+        _ => None,
+    })
     .parse(i)
 }
 
-pub fn string_literal(i: Span) -> Result<String> {
-    lexeme(preceded(
-        tag("\""),
-        many_till(satisfy(|_| true), tag("\"")).map(|(chars, _)| {
-            chars
-                .into_iter()
-                .map(|c| c.to_string())
-                .collect::<Vec<String>>()
-                .join("")
-                .to_owned()
-        }),
-    ))
+fn float(i: Tokens) -> Result<f32> {
+    next(|token| match token {
+        Token::Float(float) => Some(*float),
+        _ => None,
+    })
+    .parse(i)
+}
+
+pub fn string_literal(i: Tokens) -> Result<String> {
+    next(|token| match token {
+        Token::String(string) => Some(string.clone()),
+        _ => None,
+    })
+    .parse(i)
+}
+
+fn record_literal(i: Tokens) -> Result<Expr> {
+    located(
+        brackets(
+            Symbol::OpenSquigglyParen,
+            record_fields(separated_list0(
+                symbol(Symbol::Comma),
+                separated_pair(located(value_identifier), symbol(Symbol::Colon), expression),
+            )),
+            Symbol::CloseSquigglyParen,
+        )
+        .map(Expr_::Record),
+    )
     .parse(i)
 }
 
-fn record_literal(i: Span) -> Result<Expr> {
+fn record_update(i: Tokens) -> Result<Expr> {
     located(
-        delimited(
-            symbol("{"),
-            separated_list0(
-                symbol(","),
-                separated_pair(value_identifier, symbol(":"), expression),
+        brackets(
+            Symbol::OpenSquigglyParen,
+            separated_pair(
+                term,
+                symbol(Symbol::Alt),
+                record_fields(separated_list1(
+                    symbol(Symbol::Comma),
+                    separated_pair(located(value_identifier), symbol(Symbol::Eq), expression),
+                )),
             ),
-            symbol("}"),
+            Symbol::CloseSquigglyParen,
         )
-        .map(|fields| Expr_::Record(fields.into_iter().collect::<HashMap<String, Expr>>())),
+        .map(|(record, fields)| Expr_::Update(Box::new(record), fields)),
     )
     .parse(i)
 }
 
-fn list(i: Span) -> Result<Expr> {
+fn list(i: Tokens) -> Result<Expr> {
     located(
-        delimited(
-            symbol("["),
-            separated_list0(symbol(","), expression),
-            symbol("]"),
+        brackets(
+            Symbol::OpenSquareParen,
+            separated_list0(symbol(Symbol::Comma), expression),
+            Symbol::CloseSquareParen,
         )
         .map(Expr_::List),
     )
     .parse(i)
 }
 
-fn qualified(i: Span) -> Result<Expr> {
+fn qualified(i: Tokens) -> Result<Expr> {
     located(qualified_help).parse(i)
 }
 
-fn qualified_help(i: Span) -> Result<Expr_> {
+fn qualified_help(i: Tokens) -> Result<Expr_> {
     let (i, mut module) = module_name.parse(i)?;
-    let (i, value) = opt(preceded(symbol("."), value_identifier)).parse(i)?;
+    let (i, value) = opt(preceded(symbol(Symbol::Period), value_identifier)).parse(i)?;
     success(match value {
         Some(v) => Expr_::QualifiedIdentifier(module, v),
         None => {
@@ -82,30 +98,48 @@ fn qualified_help(i: Span) -> Result<Expr_> {
                 .0
                 .pop()
                 .expect("module_name wouldn't have parsed if it were empty");
-            Expr_::QualifiedConstructor(module, constructor)
+            if module.0.is_empty() {
+                Expr_::Constructor(constructor)
+            } else {
+                Expr_::QualifiedConstructor(module, constructor)
+            }
         }
     })
     .parse(i)
 }
 
-fn factor(i: Span) -> Result<Expr> {
+fn factor(i: Tokens) -> Result<Expr> {
     alt((
         qualified,
+        tuple_expression,
         parens(expression),
+        record_update,
         record_literal,
         list,
         identifier,
         constructor,
         located((string_literal).map(Expr_::String)),
         located((int).map(Expr_::Int)),
-        located(lexeme(number::complete::float).map(Expr_::Float)),
+        located((float).map(Expr_::Float)),
     ))
     .parse(i)
 }
 
-fn term(i: Span) -> Result<Expr> {
-    let (i, func) = factor(i)?;
-    let (i, args) = many0(factor).parse(i)?;
+// field access binds tighter than function application, so `f r.x` is `f (r.x)`.
+fn access(i: Tokens) -> Result<Expr> {
+    let (i, record) = factor(i)?;
+    let (i, fields) =
+        many0(located(preceded(symbol(Symbol::Period), value_identifier))).parse(i)?;
+    success(fields.into_iter().fold(record, |record, field| Located {
+        region: record.region.merge(&field.region),
+        inner: Expr_::Access(Box::new(record), field.inner),
+    }))
+    .parse(i)
+}
+
+fn term(i: Tokens) -> Result<Expr> {
+    let (i, func) = access(i)?;
+    let (i, args) = many0(access).parse(i)?;
 
     if args.is_empty() {
         success(func).parse(i)
@@ -121,30 +155,11 @@ fn term(i: Span) -> Result<Expr> {
     }
 }
 
-fn operator(i: Span) -> Result<Operator> {
-    use Operator::*;
-    alt((
-        symbol("<<").map(|_| Compose),
-        symbol(">>").map(|_| ComposeRev),
-        symbol("<|").map(|_| Pipe),
-        symbol("|>").map(|_| PipeRev),
-        symbol("||").map(|_| Or),
-        symbol("&&").map(|_| And),
-        symbol("==").map(|_| Eq),
-        symbol("!=").map(|_| Neq),
-        symbol("::").map(|_| Cons),
-        symbol("<=").map(|_| LTE),
-        symbol("<").map(|_| LT),
-        symbol(">=").map(|_| GTE),
-        symbol(">").map(|_| GT),
-        symbol("++").map(|_| Concat),
-        symbol("+").map(|_| Plus),
-        symbol("-").map(|_| Minus),
-        symbol("*").map(|_| Times),
-        symbol("/").map(|_| Divide),
-        symbol("%").map(|_| Mod),
-        symbol("^").map(|_| Power),
-    ))
+fn operator(i: Tokens) -> Result<Operator> {
+    next(|token| match token {
+        Token::Operator(op) => Some(*op),
+        _ => None,
+    })
     .parse(i)
 }
 
@@ -196,9 +211,9 @@ fn operator_expression_help(
     return Some(lhs);
 }
 
-fn operator_expression(i: Span) -> Result<Expr> {
+fn operator_expression(i: Tokens) -> Result<Expr> {
     let (i, first) = term.parse(i)?;
-    let (i, (mut ops, mut exprs)): (Span, (VecDeque<Operator>, VecDeque<Expr>)) =
+    let (i, (mut ops, mut exprs)): (Tokens, (VecDeque<Operator>, VecDeque<Expr>)) =
         many1(tuple((operator, term)))
             .map(|pairs| pairs.into_iter().unzip())
             .parse(i)?;
@@ -206,45 +221,43 @@ fn operator_expression(i: Span) -> Result<Expr> {
     success(operator_expression_help(&mut ops, &mut exprs, first, 0).unwrap()).parse(i)
 }
 
-fn let_from(i: Span) -> Result<Expr> {
+fn let_from(i: Tokens) -> Result<Expr> {
     located(let_from_help).parse(i)
 }
 
-fn let_from_help(i: Span) -> Result<Expr_> {
-    let (i, name) = located(delimited(keyword("let"), value_identifier, symbol("<-"))).parse(i)?;
-    let (i, value) = terminated(expression, symbol(";")).parse(i)?;
+fn let_from_help(i: Tokens) -> Result<Expr_> {
+    let (i, pattern) = delimited(
+        keyword(Keyword::Let),
+        pattern::pattern,
+        symbol(Symbol::BackArrow),
+    )
+    .parse(i)?;
+    let (i, value) = terminated(expression, symbol(Symbol::Semicolon)).parse(i)?;
     let (i, body) = expression.parse(i)?;
 
-    success(Expr_::Bind(
-        Located {
-            inner: Pattern_::Identifier(name.inner),
-            region: name.region,
-        },
-        Box::new(value),
-        Box::new(body),
-    ))
-    .parse(i)
+    success(Expr_::Bind(pattern, Box::new(value), Box::new(body))).parse(i)
 }
 
-fn let_in(i: Span) -> Result<Expr> {
+fn let_in(i: Tokens) -> Result<Expr> {
     located(let_in_help).parse(i)
 }
 
-fn let_in_help(i: Span) -> Result<Expr_> {
-    let (i, name) = located(delimited(keyword("let"), value_identifier, symbol("="))).parse(i)?;
-    let (i, value) = terminated(expression, symbol(";")).parse(i)?;
+fn let_in_help(i: Tokens) -> Result<Expr_> {
+    let (i, pattern) =
+        delimited(keyword(Keyword::Let), pattern::pattern, symbol(Symbol::Eq)).parse(i)?;
+    let (i, value) = terminated(expression, symbol(Symbol::Semicolon)).parse(i)?;
     let (i, body) = expression.parse(i)?;
 
-    success(Expr_::Let(
-        name.map(|n| Pattern_::Identifier(n)),
-        Box::new(value),
-        Box::new(body),
-    ))
-    .parse(i)
+    success(Expr_::Let(pattern, Box::new(value), Box::new(body))).parse(i)
 }
 
-fn lambda(i: Span) -> Result<Expr> {
-    let (i, args) = delimited(symbol("\\"), many1(pattern::pattern), symbol("->")).parse(i)?;
+fn lambda(i: Tokens) -> Result<Expr> {
+    let (i, args) = delimited(
+        symbol(Symbol::Backslash),
+        many1(pattern::pattern),
+        symbol(Symbol::Arrow),
+    )
+    .parse(i)?;
     let (i, body) = expression.parse(i)?;
 
     let mut args = args.into_iter().rev();
@@ -262,14 +275,14 @@ fn lambda(i: Span) -> Result<Expr> {
     .parse(i)
 }
 
-fn if_expr(i: Span) -> Result<Expr> {
+fn if_expr(i: Tokens) -> Result<Expr> {
     located(if_expr_help).parse(i)
 }
 
-fn if_expr_help(i: Span) -> Result<Expr_> {
-    let (i, cond) = preceded(keyword("if"), expression).parse(i)?;
-    let (i, then_branch) = preceded(keyword("then"), expression).parse(i)?;
-    let (i, else_branch) = preceded(keyword("else"), expression).parse(i)?;
+fn if_expr_help(i: Tokens) -> Result<Expr_> {
+    let (i, cond) = preceded(keyword(Keyword::If), expression).parse(i)?;
+    let (i, then_branch) = preceded(keyword(Keyword::Then), expression).parse(i)?;
+    let (i, else_branch) = preceded(keyword(Keyword::Else), expression).parse(i)?;
     success(Expr_::If(
         Box::new(cond),
         Box::new(then_branch),
@@ -278,22 +291,25 @@ fn if_expr_help(i: Span) -> Result<Expr_> {
     .parse(i)
 }
 
-fn alternative(i: Span) -> Result<(Pattern, Expr)> {
+fn alternative(i: Tokens) -> Result<(Pattern, Expr)> {
     use pattern::*;
-    let (i, pat) = terminated(pattern, symbol("->")).parse(i)?;
+    let (i, pat) = context("when branch", terminated(pattern, symbol(Symbol::Arrow))).parse(i)?;
     let (i, body) = expression.parse(i)?;
     success((pat, body)).parse(i)
 }
 
-fn when_expr(i: Span) -> Result<Expr> {
+fn when_expr(i: Tokens) -> Result<Expr> {
     located(when_expr_help).parse(i)
 }
 
-fn when_expr_help(i: Span) -> Result<Expr_> {
-    let (i, val) = delimited(keyword("when"), expression, keyword("is")).parse(i)?;
-    let (i, _) = opt(symbol("|")).parse(i)?;
-    let (i, alternatives) =
-        terminated(separated_list1(symbol("|"), alternative), symbol(";")).parse(i)?;
+fn when_expr_help(i: Tokens) -> Result<Expr_> {
+    let (i, val) = delimited(keyword(Keyword::When), expression, keyword(Keyword::Is)).parse(i)?;
+    let (i, _) = opt(symbol(Symbol::Alt)).parse(i)?;
+    let (i, alternatives) = terminated(
+        separated_list1(symbol(Symbol::Alt), cut(alternative)),
+        symbol(Symbol::Semicolon),
+    )
+    .parse(i)?;
     success(Expr_::When(
         Box::new(val),
         Box::new(alternatives[0].clone()), // NOTE: `alternatives` must have at least 1 element because of the seperated_list1 combinator
@@ -302,9 +318,9 @@ fn when_expr_help(i: Span) -> Result<Expr_> {
     .parse(i)
 }
 
-fn crash_expr(i: Span) -> Result<Expr> {
+fn crash_expr(i: Tokens) -> Result<Expr> {
     let (i, kw) =
-        located(keyword("crash").map(|_| Expr_::External("crash".to_owned()))).parse(i)?;
+        located(keyword(Keyword::Crash).map(|_| Expr_::External("crash".to_owned()))).parse(i)?;
     let (i, msg) = located(string_literal.map(|s| Expr_::String(s))).parse(i)?;
     success(Located {
         region: kw.region.merge(&msg.region),
@@ -313,24 +329,32 @@ fn crash_expr(i: Span) -> Result<Expr> {
     .parse(i)
 }
 
-fn extern_expr(i: Span) -> Result<Expr> {
-    located(preceded(keyword("extern"), string_literal).map(|name| Expr_::External(name))).parse(i)
+fn extern_expr(i: Tokens) -> Result<Expr> {
+    located(preceded(keyword(Keyword::Extern), string_literal).map(|name| Expr_::External(name)))
+        .parse(i)
 }
 
-fn tuple_expression(i: Span) -> Result<Expr> {
+fn tuple_expression(i: Tokens) -> Result<Expr> {
     located(tuple_expression_help).parse(i)
 }
 
-fn tuple_expression_help(i: Span) -> Result<Expr_> {
-    let (i, first) = delimited(symbol("("), expression, symbol(",")).parse(i)?;
-    let (i, mut rest) =
-        terminated(separated_list1(symbol(","), expression), symbol(")")).parse(i)?;
+fn tuple_expression_help(i: Tokens) -> Result<Expr_> {
+    let (i, (first, mut rest)) = brackets(
+        Symbol::OpenParen,
+        separated_pair(
+            expression,
+            symbol(Symbol::Comma),
+            separated_list1(symbol(Symbol::Comma), expression),
+        ),
+        Symbol::CloseParen,
+    )
+    .parse(i)?;
     let mut exprs = vec![first];
     exprs.append(&mut rest);
     Ok((i, Expr_::Tuple(exprs)))
 }
 
-pub fn expression(i: Span) -> Result<Expr> {
+pub fn expression(i: Tokens) -> Result<Expr> {
     context(
         "expression",
         alt((
@@ -2,212 +2,351 @@ mod expression;
 mod pattern;
 mod statement;
 mod tipe;
+mod token;
 
 use crate::{
     ast::{
-        source::{Export, Expr, Module, Statement, Type, TypeDefinition, Union, Value},
+        source::{
+            Declaration, Duplicate, Export, Expr, Module, Statement, Type, TypeDefinition, Union,
+            Value,
+        },
         Located, ModuleName, Name, Position, Region, Span,
     },
-    report::error::syntax::Error,
+    report::error::syntax::{self, Error},
 };
 use std::collections::HashMap;
 
 use nom::{
     branch::alt,
-    bytes::complete::tag,
-    character::complete::{alphanumeric1, digit1, multispace0, satisfy},
-    combinator::{eof, fail, not, opt, success},
+    character::complete::{digit1, multispace0, satisfy},
+    combinator::{complete, cut, fail, opt, success, verify},
     error::*,
-    multi::{many0, many1, many_m_n, many_till, separated_list0, separated_list1},
-    number,
+    multi::{many0, many1, separated_list0, separated_list1},
     sequence::{delimited, preceded, separated_pair, terminated, tuple},
-    AsChar, Compare, IResult, Input, Parser,
+    Finish, IResult, Input, Parser,
 };
-use nom_locate::{position, LocatedSpan};
 
-pub type Result<'a, O> = IResult<Span<'a>, O, Error>;
+use token::{Keyword, Symbol};
+pub use token::{Token, Tokens};
 
-const KEYWORDS: [&str; 11] = [
-    "if", "then", "else", "when", "is", "let", "module", "import", "crash", "dbg", "extern",
-];
+pub type Result<'a, O> = IResult<Tokens<'a>, O, Error>;
 
-pub fn located<'a, O, E, F>(mut parser: F) -> impl Parser<Span<'a>, Output = Located<O>, Error = E>
+// splits the code of a module into tokens, and then parses those.
+pub fn module(code: &str) -> std::result::Result<Module, Vec<Error>> {
+    let (_, tokens) = token::tokenize(Span::new(code))
+        .finish()
+        .map_err(|error| vec![error])?;
+    let (module, errors) = complete(file)
+        .parse(Tokens::new(&tokens))
+        .finish()
+        .map(|(_, parsed)| parsed)
+        .map_err(|error| vec![error])?;
+    if errors.is_empty() {
+        Ok(module)
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn located<'a, O, E, F>(
+    mut parser: F,
+) -> impl Parser<Tokens<'a>, Output = Located<O>, Error = E>
 where
-    F: Parser<Span<'a>, Output = O, Error = E>,
-    E: ParseError<Span<'a>>,
+    F: Parser<Tokens<'a>, Output = O, Error = E>,
+    E: ParseError<Tokens<'a>>,
 {
-    move |i: Span<'a>| {
-        let (i, start) = position(i)?;
+    move |i: Tokens<'a>| {
+        let start = i.start();
         let (i, inside) = parser.parse(i)?;
-        let (i, end) = position(i)?;
+        // a parser which didn't read any tokens covers nothing.
+        let end = std::cmp::max_by(start.clone(), i.previous_end(), |a, b| {
+            a.partial_cmp(b).unwrap()
+        });
         Ok((
             i,
             Located {
-                region: Region {
-                    start: Position {
-                        line: start.location_line() as usize,
-                        column: start.get_column(),
-                    },
-                    end: Position {
-                        line: end.location_line() as usize,
-                        column: end.get_column(),
-                    },
-                },
+                region: Region { start, end },
                 inner: inside,
             },
         ))
     }
 }
 
-pub fn lexeme<I, O, E, F>(parser: F) -> impl Parser<I, Output = O, Error = E>
+// reads the next token if `select` picks something out of it.
+fn next<'a, O, F>(select: F) -> impl Parser<Tokens<'a>, Output = O, Error = Error>
 where
-    F: Parser<I, Output = O, Error = E>,
-    <I as Input>::Item: AsChar,
-    I: Input + Clone,
-    E: ParseError<I>,
+    F: Fn(&'a Token) -> Option<O>,
 {
-    delimited(multispace0, parser, multispace0)
+    move |i: Tokens<'a>| match i.first().and_then(|token| select(&token.inner)) {
+        Some(output) => Ok((i.take_from(1), output)),
+        None => Err(nom::Err::Error(Error::from_error_kind(i, ErrorKind::Tag))),
+    }
 }
 
-fn keyword<'a, I, E>(kw: &'static str) -> impl Parser<I, Output = (), Error = E>
-where
-    <I as Input>::Item: AsChar,
-    I: Input + Clone + Compare<&'static str>,
-    E: ParseError<I>,
-{
-    lexeme(tag(kw)).map(|_| ())
+fn keyword<'a>(kw: Keyword) -> impl Parser<Tokens<'a>, Output = (), Error = Error> {
+    next(move |token| (*token == Token::Keyword(kw)).then_some(()))
+}
+
+fn symbol<'a>(s: Symbol) -> impl Parser<Tokens<'a>, Output = (), Error = Error> {
+    context(
+        "symbol",
+        context(
+            s.as_str(),
+            next(move |token| (*token == Token::Symbol(s)).then_some(())),
+        ),
+    )
 }
 
-fn symbol<'a, I, E>(s: &'static str) -> impl Parser<I, Output = (), Error = E>
+// once everything inside of a pair of brackets has been parsed, nothing else could have been meant,
+// so a missing closing bracket is reported straight away, pointing back to the bracket it was meant
+// to close.
+fn brackets<'a, O, F>(
+    open: Symbol,
+    mut parser: F,
+    close: Symbol,
+) -> impl Parser<Tokens<'a>, Output = O, Error = Error>
 where
-    <I as Input>::Item: AsChar,
-    I: Input + Clone + Compare<&'static str>,
-    E: ParseError<I> + ContextError<I>,
+    F: Parser<Tokens<'a>, Output = O, Error = Error>,
 {
-    context("symbol", context(s, lexeme(tag(s)).map(|_| ())))
+    move |i: Tokens<'a>| {
+        let (i, opening) = located(symbol(open)).parse(i)?;
+        let (i, inside) = parser.parse(i)?;
+        match symbol(close).parse(i) {
+            Ok((i, _)) => Ok((i, inside)),
+            Err(nom::Err::Error(error)) if error.inner == syntax::ErrorKind::Unexpected => {
+                Err(nom::Err::Failure(Error {
+                    region: error.region,
+                    inner: syntax::ErrorKind::Unclosed {
+                        opening: opening.region,
+                        close: close.as_str(),
+                    },
+                }))
+            }
+            Err(error) => Err(error),
+        }
+    }
 }
 
-fn parens<I, O, E, F>(parser: F) -> impl Parser<I, Output = O, Error = E>
+// the fields of a record, where giving the same field twice is reported rather than letting one of
+// them quietly win.
+fn record_fields<'a, T, F>(
+    mut parser: F,
+) -> impl Parser<Tokens<'a>, Output = HashMap<Name, T>, Error = Error>
 where
-    F: Parser<I, Output = O, Error = E>,
-    <I as Input>::Item: AsChar,
-    I: Input + Clone + Compare<&'static str>,
-    E: ParseError<I> + ContextError<I>,
+    F: Parser<Tokens<'a>, Output = Vec<(Located<Name>, T)>, Error = Error>,
 {
-    delimited(symbol("("), parser, symbol(")"))
+    move |i: Tokens<'a>| {
+        let (i, fields) = parser.parse(i)?;
+        let mut regions: HashMap<Name, Region> = HashMap::new();
+        let mut record = HashMap::new();
+        for (field, value) in fields {
+            if let Some(first) = regions.get(&field.inner) {
+                return Err(nom::Err::Failure(Error {
+                    region: field.region,
+                    inner: syntax::ErrorKind::DuplicateField {
+                        name: field.inner,
+                        first: first.clone(),
+                    },
+                }));
+            }
+            regions.insert(field.inner.clone(), field.region);
+            record.insert(field.inner, value);
+        }
+        Ok((i, record))
+    }
 }
 
-fn value_identifier(i: Span) -> Result<Name> {
-    lexeme(_value_identifier).parse(i)
+fn parens<'a, O, F>(parser: F) -> impl Parser<Tokens<'a>, Output = O, Error = Error>
+where
+    F: Parser<Tokens<'a>, Output = O, Error = Error>,
+{
+    brackets(Symbol::OpenParen, parser, Symbol::CloseParen)
 }
 
-fn _value_identifier(i: Span) -> Result<Name> {
-    let chars = |i| {
-        let (i, first) = satisfy(|c| (c.is_alphabetic() && c.is_lowercase()) || c == '_')(i)?;
-        let (i, mut rest) = many0(satisfy(|c| {
-            (c.is_alphabetic() && c.is_lowercase()) || c == '_' || c.is_numeric()
-        }))
-        .parse(i)?;
-        let (i, mut end) = many0(satisfy(|c| c == '?')).parse(i)?;
-        let mut ident: Vec<char> = vec![first];
-        ident.append(&mut rest);
-        ident.append(&mut end);
-        nom::IResult::Ok((
-            i,
-            ident
-                .into_iter()
-                .map(|c| c.to_string())
-                .collect::<Vec<String>>()
-                .join(""),
-        ))
-    };
-
-    let (i, ident) = lexeme(chars).parse(i)?;
-    if KEYWORDS.contains(&&*ident) {
-        context("non keyword identifier", fail()).parse(i)
-    } else {
-        context("identifier", success(ident)).parse(i)
+fn value_identifier(i: Tokens) -> Result<Name> {
+    if let Some(Located {
+        region,
+        inner: Token::Keyword(keyword),
+    }) = i.first()
+    {
+        return Err(nom::Err::Error(Error {
+            region: region.clone(),
+            inner: syntax::ErrorKind::KeywordAsIdentifier(keyword.as_str().to_owned()),
+        }));
     }
+    context(
+        "identifier",
+        next(|token| match token {
+            Token::ValueIdentifier(name) => Some(name.clone()),
+            _ => None,
+        }),
+    )
+    .parse(i)
 }
 
-fn type_identifier(i: Span) -> Result<Name> {
-    lexeme(_type_identifier).parse(i)
+fn type_identifier(i: Tokens) -> Result<Name> {
+    next(|token| match token {
+        Token::TypeIdentifier(name) => Some(name.clone()),
+        _ => None,
+    })
+    .parse(i)
 }
 
-fn _type_identifier(i: Span) -> Result<Name> {
-    let (i, first) = satisfy(|c| (c.is_alphabetic() && c.is_uppercase()))(i)?;
-    let (i, mut rest) = many0(satisfy(|c| c.is_alphabetic() || c.is_numeric())).parse(i)?;
-    let (i, mut end) = many0(satisfy(|c| c == '?')).parse(i)?;
-    let mut ident: Vec<char> = vec![first];
-    ident.append(&mut rest);
-    ident.append(&mut end);
-    nom::IResult::Ok((
-        i,
-        ident
-            .into_iter()
-            .map(|c| c.to_string())
-            .collect::<Vec<String>>()
-            .join(""),
-    ))
+fn doc_comment(i: Tokens) -> Result<String> {
+    next(|token| match token {
+        Token::DocComment(doc) => Some(doc.clone()),
+        _ => None,
+    })
+    .parse(i)
 }
 
-fn export(i: Span) -> Result<Export> {
+fn export(i: Tokens) -> Result<Export> {
     alt((
-        terminated(type_identifier, symbol("(..)")).map(Export::OpenType),
+        terminated(
+            type_identifier,
+            (
+                symbol(Symbol::OpenParen),
+                symbol(Symbol::DoublePeriod),
+                symbol(Symbol::CloseParen),
+            ),
+        )
+        .map(Export::OpenType),
         type_identifier.map(Export::ClosedType),
         value_identifier.map(Export::Value),
     ))
     .parse(i)
 }
 
-fn module_name(i: Span) -> Result<ModuleName> {
-    lexeme(separated_list1(symbol("."), type_identifier).map(ModuleName)).parse(i)
+fn module_name(i: Tokens) -> Result<ModuleName> {
+    separated_list1(symbol(Symbol::Period), type_identifier)
+        .map(ModuleName)
+        .parse(i)
+}
+
+// top level declarations start at the beginning of a line, which tells them apart from a `let`
+// inside of an expression.
+fn next_declaration(i: Tokens) -> Tokens {
+    let skipped = i
+        .iter_elements()
+        .skip(1)
+        .position(token::starts_declaration);
+    match skipped {
+        Some(skipped) => i.take_from(skipped + 1),
+        None => i.take_from(i.input_len()),
+    }
 }
 
-pub fn file(i: Span) -> Result<Module> {
-    let (i, mod_name) = preceded(keyword("module"), module_name).parse(i)?;
+// a statement which can't be parsed is skipped up to the next declaration, so that every syntax
+// error in the module is found at once. The errors are returned alongside what could be parsed.
+pub fn file(i: Tokens) -> Result<(Module, Vec<Error>)> {
+    let (i, mod_name) = preceded(keyword(Keyword::Module), located(module_name)).parse(i)?;
 
     let (i, exports) = terminated(
-        delimited(
-            symbol("["),
-            separated_list0(symbol(","), export),
-            symbol("]"),
+        brackets(
+            Symbol::OpenSquareParen,
+            separated_list0(symbol(Symbol::Comma), export),
+            Symbol::CloseSquareParen,
         ),
-        symbol(";"),
+        symbol(Symbol::Semicolon),
     )
     .parse(i)?;
 
-    let (i, statements) = many_till(statement::parse_statement, eof)
-        .map(|r| r.0)
-        .parse(i)?;
+    let mut i = i;
+    let mut statements = vec![];
+    let mut errors = vec![];
+    while i.input_len() > 0 {
+        match (opt(doc_comment), statement::parse_statement).parse(i) {
+            Ok((rest, statement)) => {
+                statements.push(statement);
+                i = rest;
+            }
+            Err(nom::Err::Error(error) | nom::Err::Failure(error)) => {
+                errors.push(error);
+                i = next_declaration(i);
+            }
+            Err(nom::Err::Incomplete(_)) => {
+                errors.push(Error::from_error_kind(i, ErrorKind::Complete));
+                i = next_declaration(i);
+            }
+        }
+    }
 
     let mut imports = vec![];
     let mut types = HashMap::new();
     let mut annotations = HashMap::new();
     let mut values = HashMap::new();
+    let mut docs = HashMap::new();
 
-    for statement in statements {
+    // only the first declaration of a name is kept, the rest are recorded so that they can be
+    // reported along with where the name was first declared.
+    let mut declared: HashMap<(Declaration, Name), Region> = HashMap::new();
+    let mut duplicates = vec![];
+    let mut declare = |declaration, name: &Located<Name>| match declared
+        .get(&(declaration, name.inner.clone()))
+    {
+        Some(first) => {
+            duplicates.push(Duplicate {
+                declaration,
+                name: name.inner.clone(),
+                first: first.clone(),
+                second: name.region.clone(),
+            });
+            false
+        }
+        None => {
+            declared.insert((declaration, name.inner.clone()), name.region.clone());
+            true
+        }
+    };
+
+    for (doc, statement) in statements {
+        // a doc comment before an import doesn't document anything.
+        match (doc, &statement) {
+            (Some(doc), Statement::LetSignature(binding, _))
+            | (Some(doc), Statement::LetValue(binding, _))
+            | (Some(doc), Statement::LetType(binding, _)) => {
+                docs.entry(binding.inner.clone()).or_insert(doc);
+            }
+            _ => {}
+        }
         match statement {
             Statement::Import(import) => imports.push(import),
             Statement::LetSignature(binding, tipe) => {
-                annotations.insert(binding, tipe);
+                if declare(Declaration::Signature, &binding) {
+                    annotations.insert(binding.inner, tipe);
+                }
             }
             Statement::LetValue(binding, expr) => {
-                values.insert(binding, expr);
+                if declare(Declaration::Value, &binding) {
+                    values.insert(binding.inner, expr);
+                }
             }
             Statement::LetType(binding, type_def) => {
-                types.insert(binding, type_def);
+                if !declare(Declaration::Type, &binding) {
+                    continue;
+                }
+                if let TypeDefinition::Union(union) = &type_def {
+                    for variant in union.variants.iter() {
+                        declare(Declaration::Constructor, &variant.name);
+                    }
+                }
+                types.insert(binding.inner, type_def);
             }
         }
     }
 
-    success(Module {
-        name: mod_name,
-        imports,
-        exports,
-        types,
-        values,
-        annotations,
-    })
+    success((
+        Module {
+            name: mod_name,
+            imports,
+            exports,
+            types,
+            values,
+            annotations,
+            duplicates,
+            docs,
+        },
+        errors,
+    ))
     .parse(i)
 }
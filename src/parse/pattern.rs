@@ -1,41 +1,121 @@
-use crate::ast::source::{Pattern, Pattern_};
+use crate::ast::source::{Operator, Pattern, Pattern_};
 
 use super::*;
 
-fn tuple_pattern(i: Span) -> Result<Pattern> {
+fn tuple_pattern(i: Tokens) -> Result<Pattern> {
     located(tuple_pattern_help).parse(i)
 }
 
-fn tuple_pattern_help(i: Span) -> Result<Pattern_> {
-    let (i, first) = delimited(symbol("("), pattern, symbol(",")).parse(i)?;
-    let (i, mut rest) = terminated(separated_list1(symbol(","), pattern), symbol(")")).parse(i)?;
+fn tuple_pattern_help(i: Tokens) -> Result<Pattern_> {
+    let (i, (first, mut rest)) = brackets(
+        Symbol::OpenParen,
+        separated_pair(
+            pattern,
+            symbol(Symbol::Comma),
+            separated_list1(symbol(Symbol::Comma), pattern),
+        ),
+        Symbol::CloseParen,
+    )
+    .parse(i)?;
     let mut elements = vec![];
     elements.push(first);
     elements.append(&mut rest);
     Ok((i, Pattern_::Tuple(elements)))
 }
 
-fn term(i: Span) -> Result<Pattern> {
+fn term(i: Tokens) -> Result<Pattern> {
     alt((
-        located(symbol("_").map(|_| Pattern_::Wildcard)),
-        located(symbol("[]").map(|_| Pattern_::Constructor("Empty".to_owned(), vec![]))),
+        located(next(|token| {
+            (*token == Token::Wildcard).then_some(Pattern_::Wildcard)
+        })),
+        located(
+            (
+                symbol(Symbol::OpenSquareParen),
+                symbol(Symbol::CloseSquareParen),
+            )
+                .map(|_| Pattern_::EmptyList),
+        ),
         tuple_pattern,
         parens(pattern),
         located(value_identifier.map(|ident| Pattern_::Identifier(ident))),
-        located(
-            tuple((type_identifier, many0(term)))
-                .map(|(tag, args)| Pattern_::Constructor(tag, args)),
-        ),
+        located(constructor),
     ))
     .parse(i)
 }
 
-pub fn pattern(i: Span) -> Result<Pattern> {
-    let (i, mut terms) = separated_list1(symbol("::"), term).parse(i)?;
+// a constructor can be qualified with its module, just like in an expression.
+fn constructor(i: Tokens) -> Result<Pattern_> {
+    let (i, (mut module, args)) = (module_name, many0(term)).parse(i)?;
+    let constructor = module
+        .0
+        .pop()
+        .expect("module_name wouldn't have parsed if it were empty");
+    success(if module.0.is_empty() {
+        Pattern_::Constructor(constructor, args)
+    } else {
+        Pattern_::QualifiedConstructor(module, constructor, args)
+    })
+    .parse(i)
+}
+
+fn cons(i: Tokens) -> Result<()> {
+    next(|token| (*token == Token::Operator(Operator::Cons)).then_some(())).parse(i)
+}
+
+pub fn pattern(i: Tokens) -> Result<Pattern> {
+    let (i, mut terms) = separated_list1(cons, term).parse(i)?;
     let last = terms.pop().unwrap();
-    let cons = terms.into_iter().fold(last, |acc, t| Located {
+    // `::` is right associative, so the last term is the innermost tail.
+    let cons = terms.into_iter().rfold(last, |acc, t| Located {
         region: acc.region.merge(&t.region),
         inner: Pattern_::Cons(Box::new(t), Box::new(acc)),
     });
     success(cons).parse(i)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::pretty::PrettyPrint;
+
+    fn parse(code: &str) -> Pattern {
+        let (_, tokens) = token::tokenize(Span::new(code)).unwrap();
+        let (rest, pattern) = pattern(Tokens::new(&tokens)).unwrap();
+        assert!(rest.first().is_none());
+        pattern
+    }
+
+    #[test]
+    fn cons_keeps_elements_in_order() {
+        let parsed = parse("a :: b :: c :: rest");
+        assert_eq!(parsed.pretty_print(), "a :: b :: c :: rest");
+        let Pattern_::Cons(first, _) = parsed.inner else {
+            panic!("expected a cons pattern, got {:?}", parsed.inner);
+        };
+        assert!(matches!(first.inner, Pattern_::Identifier(name) if name == "a"));
+    }
+
+    #[test]
+    fn cons_ends_in_the_last_term() {
+        let parsed = parse("x :: []");
+        let Pattern_::Cons(_, tail) = parsed.inner else {
+            panic!("expected a cons pattern, got {:?}", parsed.inner);
+        };
+        assert!(matches!(tail.inner, Pattern_::EmptyList));
+    }
+
+    #[test]
+    fn constructors_can_be_qualified() {
+        let parsed = parse("Geo.Shape.Circle r :: rest");
+        assert_eq!(parsed.pretty_print(), "Geo.Shape.Circle r :: rest");
+        let Pattern_::Cons(first, _) = parsed.inner else {
+            panic!("expected a cons pattern, got {:?}", parsed.inner);
+        };
+        let Pattern_::QualifiedConstructor(module, name, args) = first.inner else {
+            panic!("expected a qualified constructor, got {:?}", first.inner);
+        };
+        assert_eq!(module.0, vec!["Geo", "Shape"]);
+        assert_eq!(name, "Circle");
+        assert_eq!(args.len(), 1);
+    }
+}
@@ -1,33 +1,64 @@
 use std::collections::HashSet;
 
 use super::{expression::expression, *};
-use crate::ast::source::{Constructor, Expr_, Statement, TypeDefinition};
+use crate::ast::source::{Alias, Constructor, Expr_, External, Import, Statement, TypeDefinition};
+
+fn import_statement(i: Tokens) -> Result<Statement> {
+    let (i, name) = preceded(keyword(Keyword::Import), located(module_name)).parse(i)?;
+    let (i, alias) = opt(preceded(keyword(Keyword::As), type_identifier)).parse(i)?;
+    let (i, exposing) = terminated(
+        opt(preceded(
+            keyword(Keyword::Exposing),
+            brackets(
+                Symbol::OpenSquareParen,
+                separated_list0(symbol(Symbol::Comma), located(export)),
+                Symbol::CloseSquareParen,
+            ),
+        )),
+        symbol(Symbol::Semicolon),
+    )
+    .parse(i)?;
 
-fn import_statement(i: Span) -> Result<Statement> {
-    delimited(keyword("import"), type_identifier, symbol(";"))
-        .map(|module| Statement::Import(ModuleName(vec![module])))
-        .parse(i)
+    Ok((
+        i,
+        Statement::Import(Import {
+            name,
+            alias,
+            exposing: exposing.unwrap_or_default(),
+        }),
+    ))
 }
 
-fn constructor(i: Span) -> Result<(String, Vec<Type>)> {
-    let (i, name) = type_identifier(i)?;
+fn constructor(i: Tokens) -> Result<(Located<String>, Vec<Type>)> {
+    let (i, name) = located(type_identifier).parse(i)?;
     let (i, args) = many0(tipe::factor).parse(i)?;
 
     success((name, args)).parse(i)
 }
 
-fn internal_type_def(i: Span) -> Result<Statement> {
-    let (i, name) = type_identifier(i)?;
-    let (i, args) = terminated(many0(value_identifier), symbol("=")).parse(i)?;
-    let (i, bar) = opt(symbol("|")).parse(i)?;
+// a single constructor without a leading `|` reads just like a type application, so it is left to
+// `alias_type_def`: `let Model = List Int;` is an alias and `let Model = | List Int;` a union.
+fn internal_type_def(i: Tokens) -> Result<Statement> {
+    let (i, name) = located(type_identifier).parse(i)?;
+    let (i, args) = terminated(many0(value_identifier), symbol(Symbol::Eq)).parse(i)?;
     let (i, constructors) = terminated(
-        separated_list1(symbol("|"), constructor).map(|constructors| {
+        alt((
+            preceded(
+                symbol(Symbol::Alt),
+                separated_list1(symbol(Symbol::Alt), constructor),
+            ),
+            verify(
+                separated_list1(symbol(Symbol::Alt), constructor),
+                |constructors: &Vec<_>| constructors.len() > 1,
+            ),
+        ))
+        .map(|constructors| {
             constructors
                 .into_iter()
                 .map(|(name, args)| Constructor { name, args })
                 .collect()
         }),
-        symbol(";"),
+        symbol(Symbol::Semicolon),
     )
     .parse(i)?;
 
@@ -43,32 +74,57 @@ fn internal_type_def(i: Span) -> Result<Statement> {
     ))
 }
 
-fn external_type_def(i: Span) -> Result<Statement> {
-    let (i, name) = type_identifier(i)?;
-    let (i, _args) = terminated(many0(value_identifier), symbol("=")).parse(i)?;
+fn external_type_def(i: Tokens) -> Result<Statement> {
+    let (i, name) = located(type_identifier).parse(i)?;
+    let (i, variables) = terminated(many0(value_identifier), symbol(Symbol::Eq)).parse(i)?;
+
+    let (i, internal_name) = delimited(
+        keyword(Keyword::Extern),
+        expression::string_literal,
+        symbol(Symbol::Semicolon),
+    )
+    .parse(i)?;
+
+    Ok((
+        i,
+        Statement::LetType(
+            name,
+            TypeDefinition::External(External {
+                variables,
+                internal_name,
+            }),
+        ),
+    ))
+}
 
-    let (i, type_def) = delimited(keyword("extern"), expression::string_literal, symbol(";"))
-        .map(|s| TypeDefinition::External(s))
-        .parse(i)?;
+// a definition whose right hand side can't be read as a list of constructors, like a record or a
+// function type, gives a new name to an existing type instead.
+fn alias_type_def(i: Tokens) -> Result<Statement> {
+    let (i, name) = located(type_identifier).parse(i)?;
+    let (i, variables) = terminated(many0(value_identifier), symbol(Symbol::Eq)).parse(i)?;
+    let (i, other) = terminated(tipe::tipe, symbol(Symbol::Semicolon)).parse(i)?;
 
-    Ok((i, Statement::LetType(name, type_def)))
+    Ok((
+        i,
+        Statement::LetType(name, TypeDefinition::Alias(Alias { variables, other })),
+    ))
 }
 
-fn let_type(i: Span) -> Result<Statement> {
-    alt((internal_type_def, external_type_def)).parse(i)
+fn let_type(i: Tokens) -> Result<Statement> {
+    alt((internal_type_def, external_type_def, alias_type_def)).parse(i)
 }
 
-fn let_signature(i: Span) -> Result<Statement> {
-    let (i, name) = terminated(value_identifier, symbol(":")).parse(i)?;
-    let (i, tipe) = terminated(tipe::tipe, symbol(";")).parse(i)?;
+fn let_signature(i: Tokens) -> Result<Statement> {
+    let (i, name) = terminated(located(value_identifier), symbol(Symbol::Colon)).parse(i)?;
+    let (i, tipe) = terminated(tipe::tipe, symbol(Symbol::Semicolon)).parse(i)?;
 
     Ok((i, Statement::LetSignature(name, tipe)))
 }
 
-fn let_value(i: Span) -> Result<Statement> {
-    let (i, name) = value_identifier(i)?;
-    let (i, params) = terminated(many0(pattern::pattern), symbol("=")).parse(i)?;
-    let (i, body) = terminated(expression, symbol(";")).parse(i)?;
+fn let_value(i: Tokens) -> Result<Statement> {
+    let (i, name) = located(value_identifier).parse(i)?;
+    let (i, params) = terminated(many0(pattern::pattern), symbol(Symbol::Eq)).parse(i)?;
+    let (i, body) = terminated(expression, symbol(Symbol::Semicolon)).parse(i)?;
 
     let rhs = if params.is_empty() {
         body
@@ -85,9 +141,9 @@ fn let_value(i: Span) -> Result<Statement> {
     Ok((i, Statement::LetValue(name, rhs)))
 }
 
-fn let_declaration(i: Span) -> Result<Statement> {
+fn let_declaration(i: Tokens) -> Result<Statement> {
     preceded(
-        keyword("let"),
+        keyword(Keyword::Let),
         alt((
             context("let value", let_value),
             context("type signature", let_signature),
@@ -97,7 +153,7 @@ fn let_declaration(i: Span) -> Result<Statement> {
     .parse(i)
 }
 
-pub fn parse_statement(i: Span) -> Result<Statement> {
+pub fn parse_statement(i: Tokens) -> Result<Statement> {
     context(
         "statement",
         alt((
@@ -2,40 +2,44 @@ use crate::ast::source::{Type, Type_};
 
 use super::*;
 
-fn unit_type(i: Span) -> Result<Type> {
-    located(symbol("()").map(|_| Type_::Unit)).parse(i)
+fn unit_type(i: Tokens) -> Result<Type> {
+    located((symbol(Symbol::OpenParen), symbol(Symbol::CloseParen)).map(|_| Type_::Unit)).parse(i)
 }
 
-fn record_type(i: Span) -> Result<Type> {
-    located(delimited(
-        symbol("{"),
-        separated_list1(
-            symbol(","),
-            tuple((value_identifier, symbol(":"), tipe)).map(|(i, _, t)| (i, t)),
-        )
-        .map(|fields| Type_::Record(fields.into_iter().collect::<HashMap<String, Type>>())),
-        symbol("}"),
+fn record_type(i: Tokens) -> Result<Type> {
+    located(brackets(
+        Symbol::OpenSquigglyParen,
+        tuple((
+            opt(terminated(value_identifier, symbol(Symbol::Alt))),
+            record_fields(separated_list1(
+                symbol(Symbol::Comma),
+                tuple((located(value_identifier), symbol(Symbol::Colon), tipe))
+                    .map(|(i, _, t)| (i, t)),
+            )),
+        ))
+        .map(|(extension, fields)| Type_::Record(fields, extension)),
+        Symbol::CloseSquigglyParen,
     ))
     .parse(i)
 }
 
-fn tuple_type(i: Span) -> Result<Type> {
+fn tuple_type(i: Tokens) -> Result<Type> {
     located(
         parens(tuple((
-            terminated(tipe, symbol(",")),
+            terminated(tipe, symbol(Symbol::Comma)),
             tipe,
-            preceded(symbol(","), separated_list0(symbol(","), tipe)),
+            many0(preceded(symbol(Symbol::Comma), tipe)),
         )))
         .map(|(first, second, rest)| Type_::Tuple(Box::new(first), Box::new(second), rest)),
     )
     .parse(i)
 }
 
-fn qualified(i: Span) -> Result<Type> {
+fn qualified(i: Tokens) -> Result<Type> {
     located(qualified_help).parse(i)
 }
 
-fn qualified_help(i: Span) -> Result<Type_> {
+fn qualified_help(i: Tokens) -> Result<Type_> {
     let (i, mut module) = module_name.parse(i)?;
     match module.0.pop() {
         Some(constructor) if module.0.is_empty() => {
@@ -46,7 +50,7 @@ fn qualified_help(i: Span) -> Result<Type_> {
     }
 }
 
-pub fn factor(i: Span) -> Result<Type> {
+pub fn factor(i: Tokens) -> Result<Type> {
     alt((
         qualified,
         located(value_identifier.map(Type_::Variable)),
@@ -58,11 +62,11 @@ pub fn factor(i: Span) -> Result<Type> {
     .parse(i)
 }
 
-pub fn term(i: Span) -> Result<Type> {
+pub fn term(i: Tokens) -> Result<Type> {
     located(term_help).parse(i)
 }
 
-fn term_help(i: Span) -> Result<Type_> {
+fn term_help(i: Tokens) -> Result<Type_> {
     let (i, cons) = factor.parse(i)?;
     let (i, mut factors) = many0(factor).parse(i)?;
     factors.reverse();
@@ -80,8 +84,8 @@ fn term_help(i: Span) -> Result<Type_> {
     }
 }
 
-fn function(i: Span) -> Result<Type> {
-    separated_list1(symbol("->"), term)
+fn function(i: Tokens) -> Result<Type> {
+    separated_list1(symbol(Symbol::Arrow), term)
         .map(|terms| {
             let mut terms = terms.into_iter().rev();
             let last = terms.next().unwrap();
@@ -93,6 +97,6 @@ fn function(i: Span) -> Result<Type> {
         .parse(i)
 }
 
-pub fn tipe(i: Span) -> Result<Type> {
+pub fn tipe(i: Tokens) -> Result<Type> {
     alt((function, term)).parse(i)
 }
@@ -1,37 +1,111 @@
-use super::{Parser, Result, Tokens};
+use std::{iter::Enumerate, slice::Iter};
 
-#[derive(Debug, Clone)]
+use nom::{
+    bytes::complete::{tag, take_till, take_while, take_while1},
+    character::complete::{anychar, char, multispace1, one_of},
+    combinator::{cond, map_opt, not, recognize},
+    multi::many_till,
+    Needed,
+};
+
+use super::*;
+use crate::{ast::source::Operator, report::error::syntax::ErrorKind, util};
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Token {
     Wildcard,
-    Nat(u32),
     Int(i32),
     Float(f32),
     String(String),
-    Identifier(String),
+    ValueIdentifier(Name),
+    TypeIdentifier(Name),
     Operator(Operator),
     Symbol(Symbol),
     Keyword(Keyword),
-}
-
-#[derive(Debug, Clone)]
-pub struct LocatedToken {
-    token: Token,
-    position: usize,
-}
-
-#[derive(Debug, Clone)]
+    // the text of a `{-| ... -}` comment, which documents the declaration after it.
+    DocComment(String),
+    // something the lexer couldn't make a token out of, which is reported once parsing gets to it.
+    Invalid(ErrorKind),
+}
+
+pub type LocatedToken = Located<Token>;
+
+impl Token {
+    // whether the token can be the last one of an operand, which decides if a following `-` is
+    // subtraction or the sign of a number, so `n-1` is `n - 1` but `f (-1)` is `f` applied to `-1`.
+    fn ends_operand(&self) -> bool {
+        match self {
+            Token::Wildcard
+            | Token::Int(_)
+            | Token::Float(_)
+            | Token::String(_)
+            | Token::ValueIdentifier(_)
+            | Token::TypeIdentifier(_) => true,
+            Token::Symbol(symbol) => matches!(
+                symbol,
+                Symbol::CloseParen | Symbol::CloseSquareParen | Symbol::CloseSquigglyParen
+            ),
+            Token::Operator(_) | Token::Keyword(_) | Token::DocComment(_) | Token::Invalid(_) => {
+                false
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Keyword {
     Module,
     Import,
+    As,
+    Exposing,
     Let,
     If,
     Then,
     Else,
     When,
     Is,
-}
-
-#[derive(Debug, Clone)]
+    Crash,
+    Dbg,
+    Extern,
+}
+
+impl Keyword {
+    const ALL: [Keyword; 13] = [
+        Keyword::Module,
+        Keyword::Import,
+        Keyword::As,
+        Keyword::Exposing,
+        Keyword::Let,
+        Keyword::If,
+        Keyword::Then,
+        Keyword::Else,
+        Keyword::When,
+        Keyword::Is,
+        Keyword::Crash,
+        Keyword::Dbg,
+        Keyword::Extern,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Keyword::Module => "module",
+            Keyword::Import => "import",
+            Keyword::As => "as",
+            Keyword::Exposing => "exposing",
+            Keyword::Let => "let",
+            Keyword::If => "if",
+            Keyword::Then => "then",
+            Keyword::Else => "else",
+            Keyword::When => "when",
+            Keyword::Is => "is",
+            Keyword::Crash => "crash",
+            Keyword::Dbg => "dbg",
+            Keyword::Extern => "extern",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Symbol {
     OpenParen,
     CloseParen,
@@ -39,289 +113,460 @@ pub enum Symbol {
     CloseSquareParen,
     OpenSquigglyParen,
     CloseSquigglyParen,
-    EmptyList,
     Eq,
     Alt,
     Arrow,
+    BackArrow,
+    Backslash,
     Semicolon,
     Period,
+    DoublePeriod,
     Comma,
     Colon,
-    DoubleColon,
 }
 
-fn lexeme<'a, F: 'a, O>(inner: F) -> impl FnMut(Tokens) -> Result<O>
-where
-    F: FnMut(Tokens) -> Result<O>,
-{
-    delimited(multispace0, inner, multispace0)
-}
-
-fn operator(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, token) = lexeme(alt((
-        tag("-").map(|_| Operator::Minus),
-        tag("<<").map(|_| Operator::Compose),
-        tag(">>").map(|_| Operator::ComposeRev),
-        tag("^").map(|_| Operator::Power),
-        tag("*").map(|_| Operator::Times),
-        tag("/").map(|_| Operator::Divide),
-        tag("%").map(|_| Operator::Mod),
-        tag("+").map(|_| Operator::Plus),
-        tag("-").map(|_| Operator::Minus),
-        tag("++").map(|_| Operator::Concat),
-        tag("==").map(|_| Operator::Eq),
-        tag("!=").map(|_| Operator::Neq),
-        tag("<").map(|_| Operator::LT),
-        tag("<=").map(|_| Operator::LTE),
-        tag(">=").map(|_| Operator::GTE),
-        tag(">=").map(|_| Operator::GT),
-        tag("&&").map(|_| Operator::And),
-        tag("||").map(|_| Operator::Or),
-        tag("<|").map(|_| Operator::Pipe),
-        tag("|>").map(|_| Operator::PipeRev),
-    )))
-    .map(Token::Operator)
-    .parse(i)?;
-
-    success(LocatedToken {
-        position: pos,
-        token,
-    })(i)
-}
-
-fn keyword<'a>(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, token) = lexeme(alt((
-        tag("module").map(|_| Keyword::Module),
-        tag("import").map(|_| Keyword::Import),
-        tag("let").map(|_| Keyword::Let),
-        tag("if").map(|_| Keyword::If),
-        tag("then").map(|_| Keyword::Then),
-        tag("else").map(|_| Keyword::Else),
-        tag("when").map(|_| Keyword::When),
-        tag("is").map(|_| Keyword::Is),
-    )))
-    .map(Token::Keyword)
-    .parse(i)?;
-
-    success(LocatedToken {
-        position: pos,
-        token,
-    })(i)
+impl Symbol {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Symbol::OpenParen => "(",
+            Symbol::CloseParen => ")",
+            Symbol::OpenSquareParen => "[",
+            Symbol::CloseSquareParen => "]",
+            Symbol::OpenSquigglyParen => "{",
+            Symbol::CloseSquigglyParen => "}",
+            Symbol::Eq => "=",
+            Symbol::Alt => "|",
+            Symbol::Arrow => "->",
+            Symbol::BackArrow => "<-",
+            Symbol::Backslash => "\\",
+            Symbol::Semicolon => ";",
+            Symbol::Period => ".",
+            Symbol::DoublePeriod => "..",
+            Symbol::Comma => ",",
+            Symbol::Colon => ":",
+        }
+    }
+}
+
+type Lexed<'a, O> = IResult<Span<'a>, O, Error>;
+
+fn word(i: Span) -> Lexed<Token> {
+    recognize((
+        satisfy(|c| c.is_alphabetic() || c == '_'),
+        take_while(|c: char| c.is_alphanumeric() || c == '_'),
+        take_while(|c| c == '?'),
+    ))
+    .map(|word: Span| {
+        let word = *word.fragment();
+        if word == "_" {
+            Token::Wildcard
+        } else if let Some(keyword) = Keyword::ALL.iter().find(|k| k.as_str() == word) {
+            Token::Keyword(*keyword)
+        } else if word.starts_with(|c: char| c.is_uppercase()) {
+            Token::TypeIdentifier(word.to_owned())
+        } else {
+            Token::ValueIdentifier(word.to_owned())
+        }
+    })
+    .parse(i)
+}
+
+fn number(signed: bool) -> impl FnMut(Span) -> Lexed<Token> {
+    move |i| {
+        map_opt(
+            recognize((
+                cond(signed, opt(char('-'))),
+                digit1,
+                opt((char('.'), digit1)),
+            )),
+            |number: Span| {
+                if number.fragment().contains('.') {
+                    number.fragment().parse().ok().map(Token::Float)
+                } else {
+                    number.fragment().parse().ok().map(Token::Int)
+                }
+            },
+        )
+        .parse(i)
+    }
+}
+
+// strings end on the line they start on, so one which is never closed only takes up the rest of
+// its line and the declarations after it can still be read.
+fn string(i: Span) -> Lexed<Token> {
+    let (rest, _) = char('"')(i)?;
+    let (rest, string) = take_till(|c| c == '"' || c == '\n')(rest)?;
+    let closed: Lexed<char> = char('"')(rest);
+    match closed {
+        Ok((rest, _)) => Ok((rest, Token::String(string.fragment().to_string()))),
+        Err(_) => Ok((rest, Token::Invalid(ErrorKind::UnterminatedString))),
+    }
+}
+
+// block comments can be nested, so that code which already has comments in it can be commented
+// out.
+fn nested_comment(i: Span) -> Lexed<()> {
+    (
+        tag("{-"),
+        many_till(alt((nested_comment, anychar.map(|_| ()))), tag("-}")),
+    )
+        .map(|_| ())
+        .parse(i)
 }
 
-fn symbol<'a>(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, token) = lexeme(alt((
-        tag("->").map(|_| Symbol::Arrow),
-        tag("[]").map(|_| Symbol::EmptyList),
-        tag("::").map(|_| Symbol::DoubleColon),
-        tag("(").map(|_| Symbol::OpenParen),
-        tag(")").map(|_| Symbol::CloseParen),
-        tag("[").map(|_| Symbol::OpenSquareParen),
-        tag("]").map(|_| Symbol::CloseSquareParen),
-        tag("{").map(|_| Symbol::OpenSquigglyParen),
-        tag("}").map(|_| Symbol::CloseSquigglyParen),
-        tag("=").map(|_| Symbol::Eq),
-        tag("|").map(|_| Symbol::Alt),
-        tag(";").map(|_| Symbol::Semicolon),
-        tag(".").map(|_| Symbol::Period),
-        tag(",").map(|_| Symbol::Comma),
-        tag(":").map(|_| Symbol::Colon),
-    )))
-    .map(Token::Symbol)
-    .parse(i)?;
-
-    success(LocatedToken {
-        position: pos,
-        token,
-    })(i)
+fn line_comment(i: Span) -> Lexed<()> {
+    (tag("--"), take_till(|c| c == '\n')).map(|_| ()).parse(i)
 }
 
-fn value_identifier(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, first) = satisfy(|c| (c.is_alphabetic() && c.is_lowercase()) || c == '_')(i)?;
-    let (i, mut rest) = many0(satisfy(|c| {
-        (c.is_alphabetic() && c.is_lowercase()) || c == '_' || c.is_numeric()
-    }))(i)?;
-    let (i, mut end) = many0(satisfy(|c| c == '?'))(i)?;
-    let mut ident: Vec<char> = vec![first];
-    ident.append(&mut rest);
-    ident.append(&mut end);
-
-    success(LocatedToken {
-        position: pos,
-        token: Token::Identifier(ident.into_iter().collect::<String>()),
-    })(i)
+fn block_comment(i: Span) -> Lexed<()> {
+    preceded(not(tag("{-|")), nested_comment).parse(i)
 }
 
-fn type_identifier(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, first) = satisfy(|c| (c.is_alphabetic() && c.is_uppercase()))(i)?;
-    let (i, mut rest) = many0(satisfy(|c| c.is_alphabetic() || c.is_numeric()))(i)?;
-    let (i, mut end) = many0(satisfy(|c| c == '?'))(i)?;
-    let mut ident: Vec<char> = vec![first];
-    ident.append(&mut rest);
-    ident.append(&mut end);
-
-    success(LocatedToken {
-        position: pos,
-        token: Token::Identifier(ident.into_iter().collect::<String>()),
-    })(i)
+// whitespace and comments other than doc comments, which are skipped between tokens.
+fn trivia(i: Span) -> Lexed<()> {
+    many0(alt((multispace1.map(|_| ()), line_comment, block_comment)))
+        .map(|_| ())
+        .parse(i)
 }
 
-fn nat(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, token) = digit1
-        .map(|digits: Span| Token::Nat(digits.parse::<u32>().unwrap()))
-        .parse(i)?;
-
-    success(LocatedToken {
-        position: pos,
-        token,
-    })(i)
-}
-
-fn int(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, token) = terminated(
-        tuple((
-            alt((tag("-").map(|_| -1), tag("+").map(|_| 1), success(1))),
-            digit1.map(|string: Span| string.parse::<i32>().unwrap()),
+fn doc_comment(i: Span) -> Lexed<Token> {
+    preceded(
+        tag("{-|"),
+        recognize(many_till(
+            alt((nested_comment, anychar.map(|_| ()))),
+            tag("-}"),
         )),
-        not(tag(".")),
-    )
-    .map(|(sign, num)| sign * num)
-    .map(Token::Int)
-    .parse(i)?;
-
-    success(LocatedToken {
-        position: pos,
-        token,
-    })(i)
-}
-
-fn string_literal(i: Span) -> Result<LocatedToken> {
-    let (i, pos) = position(i)?;
-    let (i, token) = preceded(
-        tag("\""),
-        many_till(satisfy(|_| true), tag("\""))
-            .map(|(chars, _)| Token::String(chars.into_iter().collect::<String>())),
     )
-    .parse(i)?;
-
-    success(LocatedToken {
-        position: pos,
-        token,
-    })(i)
-}
-
-fn token(i: Span) -> Result<LocatedToken> {
-    alt((
-        keyword,
-        symbol,
-        operator,
-        type_identifier,
-        value_identifier,
-        nat,
-        int,
-        string_literal,
-    ))(i)
-}
-
-pub fn tokenize<'a>(i: String) -> Result<'a, Tokens<'a>> {
-    let i = Span::new(&i);
-    let (_, tokens) = terminated(many0(token), eof)(i)?;
-    success(Tokens::new(tokens))(i)
-}
-
-pub fn unit(i: Tokens) -> Result<()> {
-    todo!()
-}
-
-pub fn nat(i: Tokens) -> Result<u32> {
-    todo!()
-}
-pub fn int(i: Tokens) -> Result<i32> {
-    todo!()
-}
-pub fn float(i: Tokens) -> Result<f32> {
-    todo!()
-}
-pub fn string(i: Tokens) -> Result<&str> {
-    todo!()
-}
-
-pub fn value_identifier(i: Tokens) -> Result<&str> {
-    todo!()
-}
-pub fn type_identifier(i: Tokens) -> Result<&str> {
-    todo!()
-}
-
-pub fn kw(k: Keyword) -> impl FnMut(Tokens) -> Result<()> {
-    move |i| todo!()
-}
-
-pub fn alt(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn backslash(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn arrow(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn back_arrow(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn wildcard(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn period(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn empty_list(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn colon(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn double_colon(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn semicolon(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn eq(i: Tokens) -> Result<()> {
-    todo!()
-}
-
-pub fn open_paren(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn close_paren(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn open_bracket(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn close_bracket(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn open_squiggly(i: Tokens) -> Result<()> {
-    todo!()
-}
-pub fn close_squiggly(i: Tokens) -> Result<()> {
-    todo!()
-}
-
-pub fn comma(i: Tokens) -> Result<()> {
-    todo!()
-}
-
-pub fn operator(i: Tokens) -> Result<Operator> {
-    todo!()
+    .map(|doc: Span| {
+        let doc = doc.fragment().strip_suffix("-}").unwrap_or(doc.fragment());
+        Token::DocComment(doc.trim().to_owned())
+    })
+    .parse(i)
+}
+
+// a comment which is never closed takes up the rest of the file, so only its opening is a token.
+fn unclosed_comment(i: Span) -> Lexed<Token> {
+    tag("{-")
+        .map(|_| Token::Invalid(ErrorKind::UnterminatedComment))
+        .parse(i)
+}
+
+fn unknown(i: Span) -> Lexed<Token> {
+    anychar
+        .map(|c| Token::Invalid(ErrorKind::UnknownCharacter(c)))
+        .parse(i)
+}
+
+fn punctuation(i: Span) -> Lexed<Token> {
+    one_of("()[]{},;\\")
+        .map(|c| {
+            Token::Symbol(match c {
+                '(' => Symbol::OpenParen,
+                ')' => Symbol::CloseParen,
+                '[' => Symbol::OpenSquareParen,
+                ']' => Symbol::CloseSquareParen,
+                '{' => Symbol::OpenSquigglyParen,
+                '}' => Symbol::CloseSquigglyParen,
+                ',' => Symbol::Comma,
+                ';' => Symbol::Semicolon,
+                _ => Symbol::Backslash,
+            })
+        })
+        .parse(i)
+}
+
+const OPERATORS: [(&str, Token); 27] = [
+    ("=", Token::Symbol(Symbol::Eq)),
+    ("|", Token::Symbol(Symbol::Alt)),
+    ("->", Token::Symbol(Symbol::Arrow)),
+    ("<-", Token::Symbol(Symbol::BackArrow)),
+    (":", Token::Symbol(Symbol::Colon)),
+    (".", Token::Symbol(Symbol::Period)),
+    ("..", Token::Symbol(Symbol::DoublePeriod)),
+    ("<<", Token::Operator(Operator::Compose)),
+    (">>", Token::Operator(Operator::ComposeRev)),
+    ("<|", Token::Operator(Operator::Pipe)),
+    ("|>", Token::Operator(Operator::PipeRev)),
+    ("||", Token::Operator(Operator::Or)),
+    ("&&", Token::Operator(Operator::And)),
+    ("==", Token::Operator(Operator::Eq)),
+    ("!=", Token::Operator(Operator::Neq)),
+    ("<", Token::Operator(Operator::LT)),
+    ("<=", Token::Operator(Operator::LTE)),
+    (">", Token::Operator(Operator::GT)),
+    (">=", Token::Operator(Operator::GTE)),
+    ("::", Token::Operator(Operator::Cons)),
+    ("++", Token::Operator(Operator::Concat)),
+    ("+", Token::Operator(Operator::Plus)),
+    ("-", Token::Operator(Operator::Minus)),
+    ("*", Token::Operator(Operator::Times)),
+    ("/", Token::Operator(Operator::Divide)),
+    ("%", Token::Operator(Operator::Mod)),
+    ("^", Token::Operator(Operator::Power)),
+];
+
+// operators are read as the longest run of operator characters, so `<=` is never `<` followed by
+// `=`, and a run which isn't an operator or symbol is an error rather than being split up.
+fn operator(i: Span) -> Lexed<Token> {
+    take_while1(|c| "+-*/%^<>=!|&:.".contains(c))
+        .map(|run: Span| {
+            let run = *run.fragment();
+            match OPERATORS.iter().find(|(spelling, _)| *spelling == run) {
+                Some((_, token)) => token.clone(),
+                None => Token::Invalid(ErrorKind::BadOperator {
+                    operator: run.to_owned(),
+                    suggestions: util::suggestions(
+                        run,
+                        OPERATORS.iter().map(|(spelling, _)| *spelling),
+                    ),
+                }),
+            }
+        })
+        .parse(i)
+}
+
+pub fn tokenize(i: Span) -> Lexed<Vec<LocatedToken>> {
+    let mut tokens: Vec<LocatedToken> = vec![];
+    let (mut i, _) = trivia(i)?;
+    while !i.fragment().is_empty() {
+        let signed = !tokens
+            .last()
+            .is_some_and(|token| token.inner.ends_operand());
+        let start = Position::from_span(i);
+        let (rest, token) = alt((
+            word,
+            number(signed),
+            string,
+            doc_comment,
+            unclosed_comment,
+            punctuation,
+            operator,
+            unknown,
+        ))
+        .parse(i)?;
+        let unclosed = token == Token::Invalid(ErrorKind::UnterminatedComment);
+        tokens.push(Located {
+            region: Region {
+                start,
+                end: Position::from_span(rest),
+            },
+            inner: token,
+        });
+        if unclosed {
+            break;
+        }
+        (i, _) = trivia(rest)?;
+    }
+
+    // a doc comment only documents the declaration right after it, anywhere else it is as good as
+    // any other comment.
+    let mut following = tokens
+        .iter()
+        .skip(1)
+        .map(starts_declaration)
+        .collect::<Vec<bool>>();
+    following.push(false);
+    let tokens = tokens
+        .into_iter()
+        .zip(following)
+        .filter(|(token, declaration)| !matches!(token.inner, Token::DocComment(_)) || *declaration)
+        .map(|(token, _)| token)
+        .collect();
+    Ok((i, tokens))
+}
+
+// a `let` or `import` at the very beginning of a line starts a new declaration.
+pub fn starts_declaration(token: &LocatedToken) -> bool {
+    token.region.start.column == 1
+        && matches!(token.inner, Token::Keyword(Keyword::Let | Keyword::Import))
+}
+
+// the input of the parsers, which keeps the tokens that have already been read so that a parser
+// can tell where the last thing it read ended.
+#[derive(Debug, Clone, Copy)]
+pub struct Tokens<'a> {
+    tokens: &'a [LocatedToken],
+    offset: usize,
+}
+
+impl<'a> Tokens<'a> {
+    pub fn new(tokens: &'a [LocatedToken]) -> Self {
+        Self { tokens, offset: 0 }
+    }
+
+    pub fn first(&self) -> Option<&'a LocatedToken> {
+        self.tokens.get(self.offset)
+    }
+
+    // where the next token starts, or where the last one ended if there are none left.
+    pub fn start(&self) -> Position {
+        match self.first() {
+            Some(token) => token.region.start.clone(),
+            None => self.previous_end(),
+        }
+    }
+
+    pub fn previous_end(&self) -> Position {
+        match self.offset.checked_sub(1) {
+            Some(previous) => self.tokens[previous].region.end.clone(),
+            None => Position::START,
+        }
+    }
+}
+
+impl<'a> Input for Tokens<'a> {
+    type Item = &'a LocatedToken;
+    type Iter = Iter<'a, LocatedToken>;
+    type IterIndices = Enumerate<Self::Iter>;
+
+    fn input_len(&self) -> usize {
+        self.tokens.len() - self.offset
+    }
+
+    fn take(&self, index: usize) -> Self {
+        Self {
+            tokens: &self.tokens[..self.offset + index],
+            offset: self.offset,
+        }
+    }
+
+    fn take_from(&self, index: usize) -> Self {
+        Self {
+            tokens: self.tokens,
+            offset: self.offset + index,
+        }
+    }
+
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        (self.take_from(index), self.take(index))
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.iter_elements().position(predicate)
+    }
+
+    fn iter_elements(&self) -> Self::Iter {
+        self.tokens[self.offset..].iter()
+    }
+
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.iter_elements().enumerate()
+    }
+
+    fn slice_index(&self, count: usize) -> std::result::Result<usize, Needed> {
+        if count <= self.input_len() {
+            Ok(count)
+        } else {
+            Err(Needed::new(count - self.input_len()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(code: &str) -> Vec<Token> {
+        let (_, tokens) = tokenize(Span::new(code)).unwrap();
+        tokens.into_iter().map(|token| token.inner).collect()
+    }
+
+    fn identifier(name: &str) -> Token {
+        Token::ValueIdentifier(name.to_owned())
+    }
+
+    #[test]
+    fn operators_are_the_longest_match() {
+        assert_eq!(
+            tokens("<= <| << < |> || | :: : .. . -> - ++ + <-"),
+            vec![
+                Token::Operator(Operator::LTE),
+                Token::Operator(Operator::Pipe),
+                Token::Operator(Operator::Compose),
+                Token::Operator(Operator::LT),
+                Token::Operator(Operator::PipeRev),
+                Token::Operator(Operator::Or),
+                Token::Symbol(Symbol::Alt),
+                Token::Operator(Operator::Cons),
+                Token::Symbol(Symbol::Colon),
+                Token::Symbol(Symbol::DoublePeriod),
+                Token::Symbol(Symbol::Period),
+                Token::Symbol(Symbol::Arrow),
+                Token::Operator(Operator::Minus),
+                Token::Operator(Operator::Concat),
+                Token::Operator(Operator::Plus),
+                Token::Symbol(Symbol::BackArrow),
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_without_spaces_are_still_split_from_operands() {
+        assert_eq!(
+            tokens("x::xs"),
+            vec![
+                identifier("x"),
+                Token::Operator(Operator::Cons),
+                identifier("xs")
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_operator_runs_are_not_split_up() {
+        assert!(matches!(
+            tokens("x*-1").as_slice(),
+            [_, Token::Invalid(ErrorKind::BadOperator { operator, .. }), _] if operator == "*-"
+        ));
+    }
+
+    #[test]
+    fn keywords_are_only_whole_words() {
+        assert_eq!(
+            tokens("let letter"),
+            vec![Token::Keyword(Keyword::Let), identifier("letter")]
+        );
+    }
+
+    #[test]
+    fn minus_is_only_a_sign_where_an_operand_is_expected() {
+        assert_eq!(
+            tokens("x-1"),
+            vec![
+                identifier("x"),
+                Token::Operator(Operator::Minus),
+                Token::Int(1)
+            ]
+        );
+        assert_eq!(
+            tokens("x * -1.5"),
+            vec![
+                identifier("x"),
+                Token::Operator(Operator::Times),
+                Token::Float(-1.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn doc_comments_are_kept_before_declarations() {
+        assert_eq!(
+            tokens("{-| docs -}\nlet"),
+            vec![
+                Token::DocComment("docs".to_owned()),
+                Token::Keyword(Keyword::Let)
+            ]
+        );
+    }
+
+    #[test]
+    fn misplaced_doc_comments_are_skipped() {
+        assert_eq!(
+            tokens("{-| header -}\nmodule x = {-| inside -} 1"),
+            tokens("module x = 1")
+        );
+    }
 }
-
@@ -0,0 +1,53 @@
+use crate::{
+    ast::{Name, Region},
+    report::{code::Source, Report},
+};
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    // each alias in the cycle along with the region of its definition, which refers to the next.
+    Recursive(Vec<(Name, Region)>),
+}
+
+impl Error {
+    pub fn to_report(&self, source: Source, file_name: &str) -> Report {
+        use crate::report::document::*;
+        match self {
+            Error::Recursive(aliases) => {
+                let mut message = match aliases.as_slice() {
+                    [(name, _)] => vec![text(&format!(
+                        "The `{}` type alias is defined in terms of itself:",
+                        name
+                    ))],
+                    _ => {
+                        let names = aliases
+                            .iter()
+                            .chain(aliases.first())
+                            .map(|(name, _)| name.clone())
+                            .collect::<Vec<String>>();
+                        vec![
+                            text("These type aliases are defined in terms of each other:"),
+                            color(
+                                Color::Yellow,
+                                text(&("    ".to_owned() + &names.join(" -> "))),
+                            ),
+                        ]
+                    }
+                };
+                for (_, region) in aliases.iter() {
+                    message.push(source.snippet(region.clone()));
+                }
+                message.push(hint(
+                    "An alias is only another name for a type, so expanding this one would never \
+                     end. A recursive type has to be a union, like `let Tree a = Leaf | Node \
+                     (Tree a) a (Tree a);`.",
+                ));
+                Report {
+                    title: "RECURSIVE TYPE ALIAS".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+        }
+    }
+}
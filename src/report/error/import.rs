@@ -0,0 +1,84 @@
+use crate::{
+    ast::{ModuleName, Region},
+    report::{code::Source, Report},
+};
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    // each module in the cycle along with the region of its import of the next one.
+    Cycle(Vec<(ModuleName, Region)>),
+    Missing {
+        module: ModuleName,
+        region: Region,
+        import: ModuleName,
+        suggestions: Vec<String>,
+    },
+}
+
+impl Error {
+    // an import cycle spans several files, so this needs the source of every module involved
+    // rather than just the one the report is about.
+    pub fn to_report<'a, F>(&self, file: F) -> Report
+    where
+        F: Fn(&ModuleName) -> (&'a str, Source<'a>),
+    {
+        use crate::report::document::*;
+        match self {
+            Error::Missing {
+                module,
+                region,
+                import,
+                suggestions,
+            } => {
+                let (path, source) = file(module);
+                let mut message = vec![
+                    text(&format!("I can't find a `{}` module to import:", import)),
+                    source.snippet(region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                message.push(hint(
+                    "Every module has to be in the project folder or be one of the built in \
+                     modules, and its file has to start with a matching `module` declaration.",
+                ));
+                Report {
+                    title: "UNKNOWN IMPORT".to_owned(),
+                    path: path.to_owned(),
+                    message: stack(message),
+                }
+            }
+            Error::Cycle(imports) => {
+                let names = imports
+                    .iter()
+                    .map(|(module, _)| module.to_string())
+                    .chain(imports.first().map(|(module, _)| module.to_string()))
+                    .collect::<Vec<String>>();
+                let (path, _) = file(&imports[0].0);
+
+                let mut message = vec![
+                    text("These modules import each other in a cycle:"),
+                    color(
+                        Color::Yellow,
+                        text(&("    ".to_owned() + &names.join(" -> "))),
+                    ),
+                ];
+                for (module, region) in imports.iter() {
+                    let (path, source) = file(module);
+                    message.push(vertical_append(vec![
+                        text(&(path.to_owned() + ":")),
+                        source.snippet(region.clone()),
+                    ]));
+                }
+                message.push(hint(
+                    "A module can't depend on itself. Try moving whatever these modules share \
+                     into a new module which they can all import.",
+                ));
+
+                Report {
+                    title: "IMPORT CYCLE".to_owned(),
+                    path: path.to_owned(),
+                    message: stack(message),
+                }
+            }
+        }
+    }
+}
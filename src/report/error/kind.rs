@@ -0,0 +1,83 @@
+use crate::{
+    ast::{Name, Region},
+    report::{code::Source, Report},
+};
+
+// a named type has to be given exactly one argument for each of the type variables it is defined
+// with, whether it is a union, an alias or an extern type.
+#[derive(Debug, Clone)]
+pub enum Error {
+    TooManyArguments {
+        region: Region,
+        name: Name,
+        expected: usize,
+        found: usize,
+    },
+    TooFewArguments {
+        region: Region,
+        name: Name,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl Error {
+    pub fn to_report(&self, source: Source, file_name: &str) -> Report {
+        use crate::report::document::*;
+        match self {
+            Error::TooManyArguments {
+                region,
+                name,
+                expected,
+                found,
+            } => Report {
+                title: "TOO MANY TYPE ARGUMENTS".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "The `{}` type takes {}, but it is given {} here:",
+                        name,
+                        arguments(*expected),
+                        found,
+                    )),
+                    source.snippet(region.clone()),
+                    hint(
+                        "A type applied to another type's arguments usually means some \
+                         parentheses are missing, like in `List (Task a)`.",
+                    ),
+                ]),
+            },
+            Error::TooFewArguments {
+                region,
+                name,
+                expected,
+                found,
+            } => Report {
+                title: "TOO FEW TYPE ARGUMENTS".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "The `{}` type needs {}, but it is only given {} here:",
+                        name,
+                        arguments(*expected),
+                        found,
+                    )),
+                    source.snippet(region.clone()),
+                    hint(&format!(
+                        "`{}` has to be given a type for each of its type variables, the way a \
+                         list is written `List Int` rather than just `List`.",
+                        name
+                    )),
+                ]),
+            },
+        }
+    }
+}
+
+fn arguments(count: usize) -> String {
+    match count {
+        0 => "no arguments".to_owned(),
+        1 => "1 argument".to_owned(),
+        _ => format!("{} arguments", count),
+    }
+}
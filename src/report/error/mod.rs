@@ -1,16 +1,52 @@
+pub mod alias;
+pub mod import;
+pub mod kind;
+pub mod module;
+pub mod naming;
+pub mod pattern;
 pub mod syntax;
 pub mod tipe;
 
-use crate::report::{code::Source, Report};
+use crate::report::{code::Source, document::Document, Report};
 
+#[derive(Debug)]
 pub enum Error {
     Syntax(syntax::Error),
+    Type(tipe::Error),
+    Naming(naming::Error),
+    Module(module::Error),
+    Pattern(pattern::Error),
+    Alias(alias::Error),
+    Kind(kind::Error),
 }
 
 impl Error {
     pub fn to_report(&self, source: Source, file_name: &str) -> Report {
         match self {
             Error::Syntax(e) => e.to_report(source, file_name),
+            Error::Type(e) => e.to_report(source, file_name),
+            Error::Naming(e) => e.to_report(source, file_name),
+            Error::Module(e) => e.to_report(source, file_name),
+            Error::Pattern(e) => e.to_report(source, file_name),
+            Error::Alias(e) => e.to_report(source, file_name),
+            Error::Kind(e) => e.to_report(source, file_name),
         }
     }
 }
+
+// lists the names which are similar to one that couldn't be found, in case it was a typo.
+pub fn did_you_mean(names: &[String]) -> Vec<Document> {
+    use crate::report::document::*;
+    if names.is_empty() {
+        return vec![];
+    }
+    vec![
+        text("These names seem close though:"),
+        vertical_append(
+            names
+                .iter()
+                .map(|name| color(Color::Yellow, text(&("    ".to_owned() + name))))
+                .collect(),
+        ),
+    ]
+}
@@ -0,0 +1,66 @@
+use crate::{
+    ast::{ModuleName, Region},
+    report::{code::Source, Report},
+};
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    // the module header says something other than what the file's path within its folder does.
+    NameMismatch {
+        region: Region,
+        expected: ModuleName,
+        found: ModuleName,
+    },
+    Duplicate {
+        region: Region,
+        module: ModuleName,
+        other: String, // the path of the file which already defines the module
+    },
+}
+
+impl Error {
+    pub fn to_report(&self, source: Source, file_name: &str) -> Report {
+        use crate::report::document::*;
+        match self {
+            Error::NameMismatch {
+                region,
+                expected,
+                found,
+            } => Report {
+                title: "MODULE NAME MISMATCH".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "This file says it is the `{}` module, but where it is means it should be \
+                         `{}`:",
+                        found, expected
+                    )),
+                    source.snippet(region.clone()),
+                    hint(&format!(
+                        "Module names follow the folders they're in, so `module {}` has to be at \
+                         `{}.pac`. Either rename the module or move the file.",
+                        found,
+                        found.0.join("/")
+                    )),
+                ]),
+            },
+            Error::Duplicate {
+                region,
+                module,
+                other,
+            } => Report {
+                title: "DUPLICATE MODULE".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!("There is already a `{}` module:", module)),
+                    source.snippet(region.clone()),
+                    text(&format!("It is defined in `{}` too.", other)),
+                    hint(
+                        "Every module needs a different name, including the built in ones. Try \
+                         renaming one of them.",
+                    ),
+                ]),
+            },
+        }
+    }
+}
@@ -0,0 +1,373 @@
+use crate::{
+    ast::{source::Declaration, ModuleName, Name, Region},
+    report::{code::Source, Report},
+};
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    UnboundVariable {
+        region: Region,
+        name: Name,
+        suggestions: Vec<String>,
+    },
+    // a local value other than a function which is defined in terms of itself.
+    CyclicValue {
+        region: Region,
+        name: Name,
+    },
+    UnknownConstructor {
+        region: Region,
+        name: Name,
+        suggestions: Vec<String>,
+    },
+    UnknownType {
+        region: Region,
+        name: Name,
+        suggestions: Vec<String>,
+    },
+    UnknownModule {
+        region: Region,
+        module: ModuleName,
+        suggestions: Vec<String>,
+    },
+    // an unqualified name which more than one of the imported modules exports or exposes.
+    Ambiguous {
+        region: Region,
+        name: Name,
+        modules: Vec<ModuleName>,
+    },
+    // a qualified name whose module exists, but isn't imported by the module using it.
+    NotImported {
+        region: Region,
+        module: ModuleName,
+    },
+    // `let x <- task;` is shorthand for `Task.and_then`, so it needs `Task` to be imported too.
+    BindWithoutTask {
+        region: Region,
+    },
+    MissingMember {
+        region: Region,
+        module: ModuleName,
+        name: Name,
+        suggestions: Vec<String>,
+    },
+    NotExported {
+        region: Region,
+        module: ModuleName,
+        name: Name,
+        // the type a constructor belongs to, when the module exports that type but not its
+        // constructors.
+        closed_type: Option<Name>,
+    },
+    // a type's constructors were asked for, but its module only exports the type itself.
+    ClosedType {
+        region: Region,
+        module: ModuleName,
+        name: Name,
+    },
+    // a type signature with no definition to go with it.
+    MissingDefinition {
+        region: Region,
+        name: Name,
+        suggestions: Vec<String>,
+    },
+    Duplicate {
+        declaration: Declaration,
+        name: Name,
+        first: Region,
+        second: Region,
+    },
+}
+
+impl Error {
+    pub fn to_report(&self, source: Source, file_name: &str) -> Report {
+        use crate::report::document::*;
+        match self {
+            Error::UnboundVariable {
+                region,
+                name,
+                suggestions,
+            } => {
+                let mut message = vec![
+                    text(&format!("I can't find a `{}` variable:", name)),
+                    source.snippet(region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                message.push(hint(
+                    "Values from other modules are qualified with the module's name, like \
+                     `List.map`, unless the import exposes them, like `import List exposing \
+                     [map];`.",
+                ));
+                Report {
+                    title: "UNBOUND VARIABLE".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+            Error::CyclicValue { region, name } => Report {
+                title: "CYCLIC VALUE".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!("The value of `{}` depends on itself:", name)),
+                    source.snippet(region.clone()),
+                    hint(
+                        "Only functions can refer to themselves. Try making this a function, or \
+                         working the value out from something else.",
+                    ),
+                ]),
+            },
+            Error::UnknownConstructor {
+                region,
+                name,
+                suggestions,
+            } => {
+                let mut message = vec![
+                    text(&format!("I can't find a `{}` constructor:", name)),
+                    source.snippet(region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                Report {
+                    title: "UNKNOWN CONSTRUCTOR".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+            Error::UnknownType {
+                region,
+                name,
+                suggestions,
+            } => {
+                let mut message = vec![
+                    text(&format!("I can't find a `{}` type:", name)),
+                    source.snippet(region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                Report {
+                    title: "UNKNOWN TYPE".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+            Error::Ambiguous {
+                region,
+                name,
+                modules,
+            } => Report {
+                title: "AMBIGUOUS NAME".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "More than one imported module has a `{}`, so I don't know which one this \
+                         is:",
+                        name
+                    )),
+                    source.snippet(region.clone()),
+                    text(&format!(
+                        "It could come from any of {}.",
+                        modules
+                            .iter()
+                            .map(|module| format!("`{}`", module))
+                            .collect::<Vec<String>>()
+                            .join(", ")
+                    )),
+                    hint(&format!(
+                        "Qualify it with the module it comes from, like `{}.{}`, or only expose it \
+                         from one of the imports.",
+                        modules[0], name
+                    )),
+                ]),
+            },
+            Error::UnknownModule {
+                region,
+                module,
+                suggestions,
+            } => {
+                let mut message = vec![
+                    text(&format!("I can't find a `{}` module:", module)),
+                    source.snippet(region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                Report {
+                    title: "UNKNOWN MODULE".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+            Error::NotImported { region, module } => Report {
+                title: "MODULE NOT IMPORTED".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "This refers to the `{}` module, but it isn't imported here:",
+                        module
+                    )),
+                    source.snippet(region.clone()),
+                    hint(&format!(
+                        "Add `import {};` to the top of the file to use it.",
+                        module
+                    )),
+                ]),
+            },
+            Error::BindWithoutTask { region } => Report {
+                title: "MODULE NOT IMPORTED".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(
+                        "This `<-` runs a task with `Task.and_then`, but the `Task` module isn't \
+                         imported here:",
+                    ),
+                    source.snippet(region.clone()),
+                    hint("Add `import Task;` to the top of the file to use it."),
+                ]),
+            },
+            Error::MissingMember {
+                region,
+                module,
+                name,
+                suggestions,
+            } => {
+                let mut message = vec![
+                    text(&format!(
+                        "The `{}` module exists, but it doesn't have a `{}`:",
+                        module, name
+                    )),
+                    source.snippet(region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                Report {
+                    title: "UNKNOWN NAME".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+            Error::NotExported {
+                region,
+                module,
+                name,
+                closed_type: Some(tipe),
+            } => Report {
+                title: "NOT EXPORTED".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "The `{}` module exports the `{}` type, but not its constructors, so `{}` \
+                         can't be used here:",
+                        module, tipe, name
+                    )),
+                    source.snippet(region.clone()),
+                    hint(&format!(
+                        "To use the constructors of `{}` in other modules, export it as `{}(..)` \
+                         at the top of `{}`.",
+                        tipe, tipe, module
+                    )),
+                ]),
+            },
+            Error::NotExported {
+                region,
+                module,
+                name,
+                closed_type: None,
+            } => Report {
+                title: "NOT EXPORTED".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "The `{}` module has a `{}`, but it doesn't export it:",
+                        module, name
+                    )),
+                    source.snippet(region.clone()),
+                    hint(&format!(
+                        "Only what's listed at the top of `{}` can be used from other modules.",
+                        module
+                    )),
+                ]),
+            },
+            Error::ClosedType {
+                region,
+                module,
+                name,
+            } => Report {
+                title: "CLOSED TYPE".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "The `{}` module exports the `{}` type, but not its constructors:",
+                        module, name
+                    )),
+                    source.snippet(region.clone()),
+                    hint(&format!(
+                        "Values of a closed type can only be made and taken apart with the \
+                         functions `{}` exports. Try exposing `{}` without the `(..)`.",
+                        module, name
+                    )),
+                ]),
+            },
+            Error::MissingDefinition {
+                region,
+                name,
+                suggestions,
+            } => {
+                let mut message = vec![
+                    text(&format!(
+                        "There is a type signature for `{}`, but no definition:",
+                        name
+                    )),
+                    source.snippet(region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                message.push(hint(&format!(
+                    "Either add a `let {} = ...;` definition or remove the signature.",
+                    name
+                )));
+                Report {
+                    title: "MISSING DEFINITION".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+            Error::Duplicate {
+                declaration,
+                name,
+                first,
+                second,
+            } => {
+                let (title, description, advice) = match declaration {
+                    Declaration::Value => (
+                        "DUPLICATE DEFINITION",
+                        format!("There are two definitions of `{}`:", name),
+                        "Every top level value needs a different name. Try renaming one of them \
+                         or removing it.",
+                    ),
+                    Declaration::Signature => (
+                        "DUPLICATE SIGNATURE",
+                        format!("There are two type signatures for `{}`:", name),
+                        "A value can only have one type signature. Try removing one of them.",
+                    ),
+                    Declaration::Type => (
+                        "DUPLICATE TYPE",
+                        format!("There are two types named `{}`:", name),
+                        "Every type in a module needs a different name. Try renaming one of them \
+                         or removing it.",
+                    ),
+                    Declaration::Constructor => (
+                        "DUPLICATE CONSTRUCTOR",
+                        format!("There are two constructors named `{}`:", name),
+                        "Constructors are used without saying which type they belong to, so \
+                         every constructor in a module needs a different name, even in different \
+                         types.",
+                    ),
+                };
+                Report {
+                    title: title.to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(vec![
+                        text(&description),
+                        source.snippet(first.clone()),
+                        source.snippet(second.clone()),
+                        hint(advice),
+                    ]),
+                }
+            }
+        }
+    }
+}
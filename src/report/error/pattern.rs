@@ -0,0 +1,84 @@
+use crate::{
+    ast::{source::Pattern, Region},
+    report::{code::Source, pretty::PrettyPrint, Report},
+};
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    Incomplete {
+        region: Region,
+        missing: Vec<Pattern>,
+    },
+    Redundant {
+        region: Region,
+        index: usize,
+    },
+    Refutable {
+        region: Region,
+        missing: Vec<Pattern>,
+    },
+}
+
+impl Error {
+    pub fn to_report(&self, source: Source, file_name: &str) -> Report {
+        use crate::report::document::*;
+        match self {
+            Error::Incomplete { region, missing } => Report {
+                title: "MISSING PATTERNS".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text("This `when` expression doesn't handle every possible value:"),
+                    source.snippet(region.clone()),
+                    text("It is missing branches for:"),
+                    examples(missing),
+                    hint(
+                        "Add a branch for each of these, or a `_` branch at the end to catch \
+                         everything else.",
+                    ),
+                ]),
+            },
+            Error::Refutable { region, missing } => {
+                Report {
+                    title: "UNSAFE PATTERN".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(vec![
+                    text("This pattern doesn't match every possible value, so it can't be used to \
+                          destructure one:"),
+                    source.snippet(region.clone()),
+                    text("It doesn't match:"),
+                    examples(missing),
+                    hint("Use a `when` expression to handle the values it doesn't match."),
+                ]),
+                }
+            }
+            Error::Redundant { region, index } => Report {
+                title: "REDUNDANT PATTERN".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "Branch {} of this `when` expression can never be reached:",
+                        index
+                    )),
+                    source.snippet(region.clone()),
+                    text("Any value it would match is already matched by an earlier branch."),
+                    hint("Remove the branch, or move it above the branch which covers it."),
+                ]),
+            },
+        }
+    }
+}
+
+fn examples(patterns: &[Pattern]) -> crate::report::document::Document {
+    use crate::report::document::*;
+    vertical_append(
+        patterns
+            .iter()
+            .map(|pattern| {
+                color(
+                    Color::Yellow,
+                    text(&("    ".to_owned() + &pattern.pretty_print())),
+                )
+            })
+            .collect(),
+    )
+}
@@ -1,5 +1,6 @@
 use crate::{
     ast::{Located, Position, Region, Span},
+    parse::Tokens,
     report::{code::Source, document::Document, Report},
 };
 
@@ -7,18 +8,51 @@ use nom::error::{ContextError, ParseError};
 
 pub type Error = Located<ErrorKind>;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ErrorKind {
     MissingSemicolon,
-    // try remove these
-    ExpectedTag,
-    Expected(String),
+    Unclosed {
+        opening: Region,
+        close: &'static str,
+    },
+    KeywordAsIdentifier(String),
+    MalformedBranch,
+    DuplicateField {
+        name: String,
+        first: Region,
+    },
+    // the lexer can't make tokens out of these, so they are found before parsing starts.
+    BadOperator {
+        operator: String,
+        suggestions: Vec<String>,
+    },
+    UnknownCharacter(char),
+    UnterminatedString,
+    UnterminatedComment,
+    Unexpected,
+}
+
+impl ErrorKind {
+    // how much an error says about what went wrong, which decides between errors found at the same
+    // place by different alternatives.
+    fn specificity(&self) -> usize {
+        match self {
+            ErrorKind::Unexpected => 0,
+            ErrorKind::MissingSemicolon
+            | ErrorKind::Unclosed { .. }
+            | ErrorKind::KeywordAsIdentifier(_)
+            | ErrorKind::MalformedBranch
+            | ErrorKind::DuplicateField { .. } => 1,
+            ErrorKind::BadOperator { .. }
+            | ErrorKind::UnknownCharacter(_)
+            | ErrorKind::UnterminatedString
+            | ErrorKind::UnterminatedComment => 2,
+        }
+    }
 }
 
 impl<'a> ParseError<Span<'a>> for Error {
     fn from_error_kind(input: Span, kind: nom::error::ErrorKind) -> Self {
-        use nom::error::ErrorKind::*;
-
         let pos = Position::from_span(input);
 
         Error {
@@ -26,7 +60,7 @@ impl<'a> ParseError<Span<'a>> for Error {
                 start: pos.clone(),
                 end: pos,
             },
-            inner: ErrorKind::ExpectedTag,
+            inner: ErrorKind::Unexpected,
         }
     }
 
@@ -38,45 +72,87 @@ impl<'a> ParseError<Span<'a>> for Error {
         }
     }
 
-    fn from_char(input: Span<'a>, c: char) -> Self {
-        let pos = Position::from_span(input);
-        Error {
-            region: Region {
-                start: pos.clone(),
-                end: pos,
+    fn or(self, other: Self) -> Self {
+        furthest(self, other)
+    }
+}
+
+impl<'a> ContextError<Span<'a>> for Error {
+    fn add_context(_input: Span<'a>, ctx: &'static str, other: Self) -> Self {
+        add_context(ctx, other)
+    }
+}
+
+impl<'a> ParseError<Tokens<'a>> for Error {
+    // a token the lexer couldn't make sense of is reported as it is, wherever parsing stops at it.
+    fn from_error_kind(input: Tokens, kind: nom::error::ErrorKind) -> Self {
+        match input.first() {
+            Some(token) => Error {
+                region: token.region.clone(),
+                inner: match &token.inner {
+                    crate::parse::Token::Invalid(error) => error.clone(),
+                    _ => ErrorKind::Unexpected,
+                },
+            },
+            None => Error {
+                region: Region {
+                    start: input.previous_end(),
+                    end: input.previous_end(),
+                },
+                inner: ErrorKind::Unexpected,
             },
-            inner: ErrorKind::Expected(c.to_string()),
         }
     }
 
-    fn or(self, other: Self) -> Self {
-        let pos1 = &self.region.end;
-        let pos2 = &other.region.end;
-        if pos1 > pos2 {
-            self
-        } else {
-            other
+    fn append(input: Tokens, kind: nom::error::ErrorKind, other: Self) -> Self {
+        let error = Error::from_error_kind(input, kind);
+        Error {
+            region: error.region.merge(&other.region),
+            inner: other.inner,
         }
     }
+
+    fn or(self, other: Self) -> Self {
+        furthest(self, other)
+    }
 }
 
-impl<'a> ContextError<Span<'a>> for Error {
-    fn add_context(_input: Span<'a>, _ctx: &'static str, other: Self) -> Self {
-        let error = match other.inner {
-            ErrorKind::ExpectedTag if _ctx == ";" => ErrorKind::MissingSemicolon,
-            _ => other.inner,
-        };
-        Located {
-            region: other.region,
-            inner: error,
+impl<'a> ContextError<Tokens<'a>> for Error {
+    fn add_context(_input: Tokens<'a>, ctx: &'static str, other: Self) -> Self {
+        add_context(ctx, other)
+    }
+}
+
+// of two alternatives which both failed, the one which got further is more likely to be what was
+// meant.
+fn furthest(error: Error, other: Error) -> Error {
+    match error.region.end.partial_cmp(&other.region.end) {
+        Some(std::cmp::Ordering::Greater) => error,
+        Some(std::cmp::Ordering::Equal)
+            if error.inner.specificity() > other.inner.specificity() =>
+        {
+            error
         }
+        _ => other,
+    }
+}
+
+fn add_context(ctx: &'static str, other: Error) -> Error {
+    let error = match other.inner {
+        ErrorKind::Unexpected if ctx == ";" => ErrorKind::MissingSemicolon,
+        ErrorKind::Unexpected if ctx == "when branch" => ErrorKind::MalformedBranch,
+        _ => other.inner,
+    };
+    Located {
+        region: other.region,
+        inner: error,
     }
 }
 
 impl Error {
     pub fn to_report(&self, source: Source, file_name: &str) -> Report {
         use crate::report::{code, document::*};
-        match self.inner {
+        match &self.inner {
             ErrorKind::MissingSemicolon => Report {
                 title: "MISSING SEMICOLON".to_owned(),
                 path: file_name.to_owned(),
@@ -86,8 +162,124 @@ impl Error {
                     hint("Add a semicolon (;) at the end."),
                 ]),
             },
-            ErrorKind::ExpectedTag => todo!(),
-            ErrorKind::Expected(_) => todo!(),
+            ErrorKind::Unclosed { opening, close } => Report {
+                title: "UNCLOSED BRACKET".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "I was expecting a `{}` to close this bracket:",
+                        close
+                    )),
+                    source.snippet(opening.clone()),
+                    text("but I got stuck here instead:"),
+                    source.snippet(self.region.clone()),
+                    hint(&format!(
+                        "Add a `{}` if it is missing. If it is there, something inside the \
+                         brackets may be out of place, like a missing comma between two elements.",
+                        close
+                    )),
+                ]),
+            },
+            ErrorKind::KeywordAsIdentifier(keyword) => Report {
+                title: "RESERVED WORD".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "`{}` is a keyword, so I can't use it as a name here:",
+                        keyword
+                    )),
+                    source.snippet(self.region.clone()),
+                    hint(&format!(
+                        "Keywords are reserved for the syntax of the language. Try a different \
+                         name, like `{}_`.",
+                        keyword
+                    )),
+                ]),
+            },
+            ErrorKind::MalformedBranch => Report {
+                title: "MALFORMED WHEN BRANCH".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text("I was partway through a `when` expression when I got stuck here:"),
+                    source.snippet(self.region.clone()),
+                    hint(
+                        "Each branch of a `when` is a pattern followed by `->` and an expression, \
+                         like `| Just x -> x`, with a `;` after the last branch.",
+                    ),
+                ]),
+            },
+            ErrorKind::DuplicateField { name, first } => Report {
+                title: "DUPLICATE FIELD".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!("This record has more than one `{}` field:", name)),
+                    source.snippet(first.merge(&self.region)),
+                    hint(
+                        "Each field of a record can only be given once. Try removing one of them.",
+                    ),
+                ]),
+            },
+            ErrorKind::BadOperator {
+                operator,
+                suggestions,
+            } => {
+                let mut message = vec![
+                    text(&format!(
+                        "I don't know what the `{}` operator is:",
+                        operator
+                    )),
+                    source.snippet(self.region.clone()),
+                ];
+                message.append(&mut super::did_you_mean(suggestions));
+                message.push(hint(
+                    "Operators next to each other are read as one, so `x*-1` has to be written \
+                     `x * -1`.",
+                ));
+                Report {
+                    title: "UNKNOWN OPERATOR".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(message),
+                }
+            }
+            ErrorKind::UnknownCharacter(c) => Report {
+                title: "UNEXPECTED CHARACTER".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!("I don't know what to do with this `{}`:", c)),
+                    source.snippet(self.region.clone()),
+                    hint("It isn't used anywhere in the language outside of strings."),
+                ]),
+            },
+            ErrorKind::UnterminatedString => Report {
+                title: "UNFINISHED STRING".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text("This string never ends:"),
+                    source.snippet(self.region.clone()),
+                    hint("Add a `\"` where the string should end."),
+                ]),
+            },
+            ErrorKind::UnterminatedComment => Report {
+                title: "UNFINISHED COMMENT".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text("This comment is never closed:"),
+                    source.snippet(self.region.clone()),
+                    hint(
+                        "Add a `-}` where the comment should end. Comments can be nested, so \
+                         every `{-` inside of it needs its own `-}` as well.",
+                    ),
+                ]),
+            },
+            ErrorKind::Unexpected => Report {
+                title: "SYNTAX PROBLEM".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text("I got stuck while parsing this part of the code:"),
+                    source.snippet(self.region.clone()),
+                    hint("There may be a typo here, or something missing just before it."),
+                ]),
+            },
         }
     }
 }
@@ -1,2 +1,313 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{canonical::Type, Name, Region},
+    report::{code::Source, pretty::PrettyPrint, Report},
+    util,
+};
+
 #[derive(Debug, Clone)]
-pub enum Error {}
+pub enum Error {
+    Mismatch {
+        region: Region,
+        expected: Type,
+        actual: Type,
+    },
+    InfiniteType {
+        region: Region,
+        variable: Name,
+        tipe: Type,
+    },
+    // a constructor given the wrong number of arguments, either in a pattern or by being applied
+    // to too many of them.
+    ConstructorArity {
+        region: Region,
+        expected: usize,
+        actual: usize,
+        pattern: bool,
+    },
+    AnnotationTooGeneral {
+        region: Region,
+        name: Name,
+        annotation: Type,
+        inferred: Type,
+    },
+    UnboundVariable {
+        region: Region,
+        name: Name,
+    },
+    MissingField {
+        region: Region,
+        tipe: Type,
+        field: Name,
+    },
+    UnboundTypeVariable {
+        region: Region,
+        tipe: Name,
+        variable: Name,
+    },
+}
+
+impl Error {
+    pub fn to_report(&self, source: Source, file_name: &str) -> Report {
+        use crate::report::document::*;
+        match self {
+            Error::Mismatch {
+                region,
+                expected,
+                actual,
+            } => {
+                let types = readable(&[expected, actual]);
+                Report {
+                    title: "TYPE MISMATCH".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(vec![
+                        text("This expression doesn't have the type I was expecting:"),
+                        source.snippet(region.clone()),
+                        text("It is:"),
+                        display(&types[1]),
+                        text("But I need:"),
+                        display(&types[0]),
+                    ]),
+                }
+            }
+            Error::InfiniteType {
+                region,
+                variable,
+                tipe,
+            } => {
+                let types = readable(&[&Type::Variable(variable.clone()), tipe]);
+                Report {
+                    title: "INFINITE TYPE".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(vec![
+                        text("I'm inferring a self-referential type for this expression:"),
+                        source.snippet(region.clone()),
+                        append(vec![
+                            text("The type variable "),
+                            color(Color::Yellow, text(&types[0].pretty_print())),
+                            text(" would need to be the same as:"),
+                        ]),
+                        display(&types[1]),
+                        hint(
+                            "This usually means a value is being used in two incompatible ways, \
+                             like a function being applied to itself.",
+                        ),
+                    ]),
+                }
+            }
+            Error::ConstructorArity {
+                region,
+                expected,
+                actual,
+                pattern: false,
+            } => Report {
+                title: "WRONG NUMBER OF ARGUMENTS".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "This constructor is given {} but it only takes {}:",
+                        arguments(*actual),
+                        arguments(*expected),
+                    )),
+                    source.snippet(region.clone()),
+                    hint(
+                        "An argument which is itself a function call needs parentheses around \
+                         it, like `Image name (width * 2) height`.",
+                    ),
+                ]),
+            },
+            Error::ConstructorArity {
+                region,
+                expected,
+                actual,
+                pattern: true,
+            } => Report {
+                title: "WRONG NUMBER OF ARGUMENTS".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "This pattern gives its constructor {} but it needs {}:",
+                        arguments(*actual),
+                        arguments(*expected),
+                    )),
+                    source.snippet(region.clone()),
+                    hint("Use _ for any argument you don't care about."),
+                ]),
+            },
+            Error::AnnotationTooGeneral {
+                region,
+                name,
+                annotation,
+                inferred,
+            } => {
+                let types = readable(&[annotation, inferred]);
+                Report {
+                    title: "TYPE ANNOTATION TOO GENERAL".to_owned(),
+                    path: file_name.to_owned(),
+                    message: stack(vec![
+                        text(&format!(
+                            "The type annotation for `{}` says it can be:",
+                            name
+                        )),
+                        display(&types[0]),
+                        text("But the definition is more specific than that:"),
+                        source.snippet(region.clone()),
+                        text("It only works as:"),
+                        display(&types[1]),
+                        hint(
+                            "A type variable in an annotation has to work for every type. \
+                             Try replacing it with the type the definition actually uses.",
+                        ),
+                    ]),
+                }
+            }
+            Error::UnboundVariable { region, name } => Report {
+                title: "UNBOUND VARIABLE".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!("I can't find a `{}` variable:", name)),
+                    source.snippet(region.clone()),
+                ]),
+            },
+            Error::MissingField {
+                region,
+                tipe,
+                field,
+            } => Report {
+                title: "MISSING FIELD".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!("This value doesn't have a `{}` field:", field)),
+                    source.snippet(region.clone()),
+                    text("Its type is:"),
+                    display(&readable(&[tipe])[0]),
+                ]),
+            },
+            Error::UnboundTypeVariable {
+                region,
+                tipe,
+                variable,
+            } => Report {
+                title: "UNBOUND TYPE VARIABLE".to_owned(),
+                path: file_name.to_owned(),
+                message: stack(vec![
+                    text(&format!(
+                        "The `{}` type uses a type variable `{}` which it never declares:",
+                        tipe, variable
+                    )),
+                    source.snippet(region.clone()),
+                    hint(&format!(
+                        "Add `{}` to the type's parameters, like `let {} {} = ...`.",
+                        variable, tipe, variable
+                    )),
+                ]),
+            },
+        }
+    }
+}
+
+fn display(tipe: &Type) -> crate::report::document::Document {
+    use crate::report::document::*;
+    color(Color::Yellow, text(&util::indent(&tipe.pretty_print())))
+}
+
+fn arguments(count: usize) -> String {
+    match count {
+        1 => "1 argument".to_owned(),
+        _ => format!("{} arguments", count),
+    }
+}
+
+// the type checker names its own type variables `?1`, `?2`, ..., so before showing types to the
+// user those are renamed to letters which aren't already used by any of the types.
+fn readable(types: &[&Type]) -> Vec<Type> {
+    let mut variables = vec![];
+    for tipe in types.iter() {
+        collect_variables(tipe, &mut variables);
+    }
+
+    let mut letters = ('a'..='z')
+        .map(|c| c.to_string())
+        .chain((1..).map(|i| format!("t{}", i)))
+        .filter(|name| !variables.contains(name));
+    // variables which can only be numbers keep saying so.
+    let mut numbers = std::iter::once("number".to_owned())
+        .chain((1..).map(|i| format!("number{}", i)))
+        .filter(|name| !variables.contains(name));
+    let names = variables
+        .iter()
+        .filter(|var| var.starts_with('?'))
+        .map(|var| {
+            let name = if var.starts_with("?number") {
+                numbers.next()
+            } else {
+                letters.next()
+            };
+            (var.clone(), name.unwrap())
+        })
+        .collect::<HashMap<Name, Name>>();
+
+    types.iter().map(|tipe| rename(tipe, &names)).collect()
+}
+
+fn collect_variables(tipe: &Type, variables: &mut Vec<Name>) {
+    match tipe {
+        Type::Variable(var) => {
+            if !variables.contains(var) {
+                variables.push(var.clone());
+            }
+        }
+        Type::Identifier(_) | Type::Unit => {}
+        Type::Application(a, b) | Type::Lambda(a, b) => {
+            collect_variables(a, variables);
+            collect_variables(b, variables);
+        }
+        Type::Record(fields, extension) => {
+            if let Some(var) = extension {
+                if !variables.contains(var) {
+                    variables.push(var.clone());
+                }
+            }
+            let mut fields = fields.iter().collect::<Vec<_>>();
+            fields.sort_by_key(|(name, _)| *name);
+            for (_, tipe) in fields {
+                collect_variables(tipe, variables);
+            }
+        }
+        Type::Tuple(first, second, rest) => {
+            collect_variables(first, variables);
+            collect_variables(second, variables);
+            for tipe in rest.iter() {
+                collect_variables(tipe, variables);
+            }
+        }
+    }
+}
+
+fn rename(tipe: &Type, names: &HashMap<Name, Name>) -> Type {
+    match tipe {
+        Type::Variable(var) => Type::Variable(names.get(var).unwrap_or(var).clone()),
+        Type::Identifier(_) | Type::Unit => tipe.clone(),
+        Type::Application(cons, arg) => {
+            Type::Application(Box::new(rename(cons, names)), Box::new(rename(arg, names)))
+        }
+        Type::Lambda(arg, ret) => {
+            Type::Lambda(Box::new(rename(arg, names)), Box::new(rename(ret, names)))
+        }
+        Type::Record(fields, extension) => Type::Record(
+            fields
+                .iter()
+                .map(|(field, tipe)| (field.clone(), rename(tipe, names)))
+                .collect(),
+            extension
+                .as_ref()
+                .map(|var| names.get(var).unwrap_or(var).clone()),
+        ),
+        Type::Tuple(first, second, rest) => Type::Tuple(
+            Box::new(rename(first, names)),
+            Box::new(rename(second, names)),
+            rest.iter().map(|tipe| rename(tipe, names)).collect(),
+        ),
+    }
+}
@@ -22,7 +22,9 @@ impl Report {
                     text("-- "),
                     text(&self.title),
                     text(" "),
-                    text(&"-".repeat(width as usize - 8 - self.title.len() - self.path.len())),
+                    text(&"-".repeat(
+                        (width as usize).saturating_sub(8 + self.title.len() + self.path.len()),
+                    )),
                     text(" "),
                     text(&self.path),
                     text(" --"),
@@ -1,5 +1,8 @@
 use crate::{
-    ast::source::{Expr, Expr_, Operator, Pattern, Pattern_, Type, Type_},
+    ast::{
+        canonical,
+        source::{Expr, Expr_, Operator, Pattern, Pattern_, Type, Type_},
+    },
     util::indent,
 };
 
@@ -15,8 +18,12 @@ impl PrettyPrint for Type {
             Type_::Fn(arg, ret) => {
                 "(".to_owned() + &arg.pretty_print() + " -> " + &ret.pretty_print() + ")"
             }
-            Type_::Record(fields) => {
+            Type_::Record(fields, extension) => {
                 "{".to_owned()
+                    + &extension
+                        .as_ref()
+                        .map(|extension| extension.to_owned() + " | ")
+                        .unwrap_or_default()
                     + &fields
                         .iter()
                         .map(|(name, value)| name.to_owned() + ": " + &value.pretty_print())
@@ -52,6 +59,59 @@ impl PrettyPrint for Type {
     }
 }
 
+impl PrettyPrint for canonical::Type {
+    fn pretty_print(&self) -> String {
+        use canonical::Type;
+        let argument = |tipe: &Type| match tipe {
+            Type::Application(_, _) | Type::Lambda(_, _) => {
+                "(".to_owned() + &tipe.pretty_print() + ")"
+            }
+            _ => tipe.pretty_print(),
+        };
+        match self {
+            Type::Variable(name) => name.to_owned(),
+            Type::Identifier(name) => name.get().to_owned(),
+            Type::Application(cons, arg) => {
+                let cons = match &**cons {
+                    Type::Application(_, _) => cons.pretty_print(),
+                    _ => argument(cons),
+                };
+                cons + " " + &argument(arg)
+            }
+            Type::Lambda(arg, ret) => {
+                let arg = match &**arg {
+                    Type::Lambda(_, _) => "(".to_owned() + &arg.pretty_print() + ")",
+                    _ => arg.pretty_print(),
+                };
+                arg + " -> " + &ret.pretty_print()
+            }
+            Type::Record(fields, extension) => {
+                let mut fields = fields
+                    .iter()
+                    .map(|(name, tipe)| name.to_owned() + " : " + &tipe.pretty_print())
+                    .collect::<Vec<String>>();
+                fields.sort();
+                let extension = match extension {
+                    Some(extension) => extension.to_owned() + " | ",
+                    None => "".to_owned(),
+                };
+                "{ ".to_owned() + &extension + &fields.join(", ") + " }"
+            }
+            Type::Unit => "()".to_owned(),
+            Type::Tuple(first, second, rest) => {
+                "(".to_owned()
+                    + &std::iter::once(&**first)
+                        .chain(std::iter::once(&**second))
+                        .chain(rest.iter())
+                        .map(|tipe| tipe.pretty_print())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                    + ")"
+            }
+        }
+    }
+}
+
 impl PrettyPrint for Operator {
     fn pretty_print(&self) -> String {
         use Operator::*;
@@ -81,23 +141,31 @@ impl PrettyPrint for Operator {
     }
 }
 
+fn constructor_pattern(tag: String, patterns: &[Pattern]) -> String {
+    std::iter::once(tag)
+        .chain(patterns.iter().map(|pattern| match &pattern.inner {
+            Pattern_::Constructor(_, args) | Pattern_::QualifiedConstructor(_, _, args)
+                if !args.is_empty() =>
+            {
+                "(".to_owned() + &pattern.pretty_print() + ")"
+            }
+            Pattern_::Cons(_, _) => "(".to_owned() + &pattern.pretty_print() + ")",
+            _ => pattern.pretty_print(),
+        }))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
 impl PrettyPrint for Pattern {
     fn pretty_print(&self) -> String {
         match &self.inner {
             Pattern_::Wildcard => "_".to_string(),
             Pattern_::Identifier(ident) => ident.to_string(),
-            Pattern_::Constructor(tag, patterns) => match &**tag {
-                "Empty" => "[]".to_owned(),
-                // TODO: cons
-                _ => {
-                    tag.to_owned()
-                        + &patterns
-                            .iter()
-                            .map(|pattern| pattern.pretty_print())
-                            .collect::<Vec<String>>()
-                            .join(" ")
-                }
-            },
+            Pattern_::Constructor(tag, patterns) => constructor_pattern(tag.to_owned(), patterns),
+            Pattern_::QualifiedConstructor(module, tag, patterns) => {
+                constructor_pattern(module.0.join(".") + "." + tag, patterns)
+            }
+            Pattern_::EmptyList => "[]".to_owned(),
             Pattern_::Tuple(patterns) => {
                 "(".to_owned()
                     + &patterns
@@ -107,7 +175,13 @@ impl PrettyPrint for Pattern {
                         .join(", ")
                     + ")"
             }
-            Pattern_::Cons(element, list) => element.pretty_print() + "::" + &list.pretty_print(),
+            Pattern_::Cons(element, list) => {
+                let element = match &element.inner {
+                    Pattern_::Cons(_, _) => "(".to_owned() + &element.pretty_print() + ")",
+                    _ => element.pretty_print(),
+                };
+                element + " :: " + &list.pretty_print()
+            }
         }
     }
 }
@@ -137,6 +211,18 @@ impl PrettyPrint for Expr {
                         .join(", ")
                     + " }"
             }
+            Expr_::Access(record, field) => record.pretty_print() + "." + field,
+            Expr_::Update(record, fields) => {
+                "{ ".to_owned()
+                    + &record.pretty_print()
+                    + " | "
+                    + &fields
+                        .iter()
+                        .map(|(field, value)| field.to_owned() + " = " + &value.pretty_print())
+                        .collect::<Vec<String>>()
+                        .join(", ")
+                    + " }"
+            }
             Expr_::QualifiedIdentifier(module, member) => module.0.join(".") + "." + &member,
             Expr_::QualifiedConstructor(module, member) => module.0.join(".") + "." + &member,
             Expr_::List(xs) => {
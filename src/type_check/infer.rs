@@ -0,0 +1,444 @@
+use std::collections::HashMap;
+
+use crate::{
+    ast::{
+        canonical::{Constructor, Expr, Expr_, Operator, Pattern, Pattern_, Type},
+        ModuleName, Name, Qualified, Region,
+    },
+    report::error::tipe::Error,
+};
+
+use super::{unify, Checker, Context, Scheme};
+
+fn builtin(module: &str, name: &str) -> Type {
+    Type::Identifier(Qualified::Foreign {
+        module: ModuleName(vec![module.to_owned()]),
+        member: name.to_owned(),
+    })
+}
+
+fn bool() -> Type {
+    builtin("Bool", "Bool")
+}
+
+fn string() -> Type {
+    builtin("String", "String")
+}
+
+fn list(element: Type) -> Type {
+    Type::Application(Box::new(builtin("List", "List")), Box::new(element))
+}
+
+// the constructor at the head of a chain of applications, along with how many arguments it is
+// given.
+fn applied_constructor(expr: &Expr) -> Option<(&Qualified<Constructor>, usize)> {
+    match &expr.inner {
+        Expr_::Constructor(constructor) => Some((constructor, 0)),
+        Expr_::Ap { function, .. } => {
+            applied_constructor(function).map(|(constructor, count)| (constructor, count + 1))
+        }
+        _ => None,
+    }
+}
+
+impl<'a> Checker<'a> {
+    pub fn infer(&mut self, context: &Context, expr: &Expr) -> Result<Type, Error> {
+        match &expr.inner {
+            Expr_::Variable(Qualified::Local(name)) => match context.get(name) {
+                Some(scheme) => Ok(self.instantiate(scheme)),
+                None => Err(Error::UnboundVariable {
+                    region: expr.region.clone(),
+                    name: name.clone(),
+                }),
+            },
+            Expr_::Variable(Qualified::Foreign { module, member }) => {
+                match self
+                    .interfaces
+                    .get(module)
+                    .and_then(|interface| interface.get(member))
+                {
+                    Some(scheme) => Ok(self.instantiate(scheme)),
+                    None => Err(Error::UnboundVariable {
+                        region: expr.region.clone(),
+                        name: format!("{}.{}", module, member),
+                    }),
+                }
+            }
+            // externals are given their type by the annotation of the definition using them.
+            Expr_::Variable(Qualified::Kernel(_)) => Ok(self.fresh()),
+            Expr_::Constructor(constructor) => {
+                let scheme = self.constructor(constructor);
+                Ok(self.instantiate(&scheme))
+            }
+            Expr_::Unit => Ok(Type::Unit),
+            Expr_::Bool(_) => Ok(bool()),
+            Expr_::Int(_) => Ok(builtin("Num", "Int")),
+            Expr_::Float(_) => Ok(builtin("Num", "Float")),
+            Expr_::String(_) => Ok(string()),
+            Expr_::List(elements) => {
+                let element = self.fresh();
+                for expr in elements.iter() {
+                    self.check(context, expr, &element)?;
+                }
+                Ok(list(element))
+            }
+            Expr_::Tuple(exprs) => {
+                let types = exprs
+                    .iter()
+                    .map(|expr| self.infer(context, expr))
+                    .collect::<Result<Vec<Type>, Error>>()?;
+                Ok(Type::Tuple(
+                    Box::new(types[0].clone()),
+                    Box::new(types[1].clone()),
+                    types[2..].to_vec(),
+                ))
+            }
+            Expr_::Ap { function, arg } => {
+                // constructors are curried, so they can be given fewer arguments than they take,
+                // but never more.
+                if let Some((constructor, actual)) = applied_constructor(expr) {
+                    let arity = constructor.get().arity as usize;
+                    if actual > arity {
+                        return Err(Error::ConstructorArity {
+                            region: expr.region.clone(),
+                            expected: arity,
+                            actual,
+                            pattern: false,
+                        });
+                    }
+                }
+                let function_type = self.infer(context, function)?;
+                let (arg_type, ret) = match self.apply(&function_type) {
+                    Type::Lambda(arg_type, ret) => (*arg_type, *ret),
+                    _ => {
+                        let arg_type = self.fresh();
+                        let ret = self.fresh();
+                        self.unify_at(
+                            &function.region,
+                            &Type::Lambda(Box::new(arg_type.clone()), Box::new(ret.clone())),
+                            &function_type,
+                        )?;
+                        (arg_type, ret)
+                    }
+                };
+                self.check(context, arg, &arg_type)?;
+                Ok(ret)
+            }
+            Expr_::Op { op, lhs, rhs } => {
+                let (lhs_type, rhs_type, ret) = self.operator(op);
+                self.check(context, lhs, &lhs_type)?;
+                self.check(context, rhs, &rhs_type)?;
+                Ok(ret)
+            }
+            Expr_::LetRec { defs, body } => {
+                let mut inner = context.clone();
+                let types = defs
+                    .iter()
+                    .map(|(name, _)| {
+                        let tipe = self.fresh();
+                        inner.insert(name.clone(), Scheme(vec![], tipe.clone()));
+                        tipe
+                    })
+                    .collect::<Vec<Type>>();
+                for ((_, def), tipe) in defs.iter().zip(types.iter()) {
+                    self.check(&inner, def, tipe)?;
+                }
+
+                let mut outer = context.clone();
+                for ((name, _), tipe) in defs.iter().zip(types.iter()) {
+                    outer.insert(name.clone(), self.generalize(context, tipe));
+                }
+                self.infer(&outer, body)
+            }
+            Expr_::Record(fields) => Ok(Type::Record(
+                fields
+                    .iter()
+                    .map(|(field, expr)| Ok((field.clone(), self.infer(context, expr)?)))
+                    .collect::<Result<_, Error>>()?,
+                None,
+            )),
+            Expr_::Access { record, field } => {
+                let record_type = self.infer(context, record)?;
+                self.field(&record.region, &record_type, field)
+            }
+            Expr_::Update { record, fields } => {
+                let record_type = self.infer(context, record)?;
+                for (field, expr) in fields.iter() {
+                    let field_type = self.field(&record.region, &record_type, field)?;
+                    self.check(context, expr, &field_type)?;
+                }
+                Ok(record_type)
+            }
+            Expr_::Let { .. }
+            | Expr_::Destruct { .. }
+            | Expr_::Lambda { .. }
+            | Expr_::If { .. }
+            | Expr_::When { .. } => {
+                let tipe = self.fresh();
+                self.check(context, expr, &tipe)?;
+                Ok(tipe)
+            }
+        }
+    }
+
+    // checking against a known type pushes the expectation inwards, so that mismatches are
+    // reported on the smallest expression possible instead of the whole definition.
+    pub fn check(&mut self, context: &Context, expr: &Expr, expected: &Type) -> Result<(), Error> {
+        match &expr.inner {
+            Expr_::Lambda { arg, body } => {
+                let (arg_type, ret) = match self.apply(expected) {
+                    Type::Lambda(arg_type, ret) => (*arg_type, *ret),
+                    _ => {
+                        let arg_type = self.fresh();
+                        let ret = self.fresh();
+                        self.unify_at(
+                            &expr.region,
+                            expected,
+                            &Type::Lambda(Box::new(arg_type.clone()), Box::new(ret.clone())),
+                        )?;
+                        (arg_type, ret)
+                    }
+                };
+                let mut inner = context.clone();
+                inner.insert(arg.clone(), Scheme(vec![], arg_type));
+                self.check(&inner, body, &ret)
+            }
+            Expr_::If {
+                cond,
+                true_branch,
+                false_branch,
+            } => {
+                self.check(context, cond, &bool())?;
+                self.check(context, true_branch, expected)?;
+                self.check(context, false_branch, expected)
+            }
+            Expr_::Let { name, expr, body } => {
+                let tipe = self.infer(context, expr)?;
+                let mut inner = context.clone();
+                inner.insert(name.clone(), self.generalize(context, &tipe));
+                self.check(&inner, body, expected)
+            }
+            Expr_::Destruct {
+                pattern,
+                expr,
+                body,
+            } => {
+                let tipe = self.infer(context, expr)?;
+                let mut inner = context.clone();
+                self.pattern(&mut inner, pattern, &tipe)?;
+                self.check(&inner, body, expected)
+            }
+            Expr_::When {
+                expr,
+                first_alternative,
+                rest_alternatives,
+            } => {
+                let subject = self.infer(context, expr)?;
+                for (pattern, body) in
+                    std::iter::once(&**first_alternative).chain(rest_alternatives.iter())
+                {
+                    let mut inner = context.clone();
+                    self.pattern(&mut inner, pattern, &subject)?;
+                    self.check(&inner, body, expected)?;
+                }
+                Ok(())
+            }
+            _ => {
+                let actual = self.infer(context, expr)?;
+                self.unify_at(&expr.region, expected, &actual)
+            }
+        }
+    }
+
+    pub fn pattern(
+        &mut self,
+        bindings: &mut Context,
+        pattern: &Pattern,
+        expected: &Type,
+    ) -> Result<(), Error> {
+        match &pattern.inner {
+            Pattern_::Wildcard => Ok(()),
+            Pattern_::Identifier(name) => {
+                bindings.insert(name.clone(), Scheme(vec![], expected.clone()));
+                Ok(())
+            }
+            Pattern_::Constructor(constructor, args) => {
+                let arity = constructor.get().arity as usize;
+                if args.len() != arity {
+                    return Err(Error::ConstructorArity {
+                        region: pattern.region.clone(),
+                        expected: arity,
+                        actual: args.len(),
+                        pattern: true,
+                    });
+                }
+
+                let scheme = self.constructor(constructor);
+                let mut tipe = self.instantiate(&scheme);
+                let mut arg_types = vec![];
+                for _ in 0..arity {
+                    match tipe {
+                        Type::Lambda(arg, ret) => {
+                            arg_types.push(*arg);
+                            tipe = *ret;
+                        }
+                        _ => unreachable!("constructor annotations have one arrow per argument"),
+                    }
+                }
+
+                self.unify_at(&pattern.region, expected, &tipe)?;
+                for (arg, arg_type) in args.iter().zip(arg_types.iter()) {
+                    self.pattern(bindings, arg, arg_type)?;
+                }
+                Ok(())
+            }
+            Pattern_::Tuple(patterns) => {
+                let types = patterns.iter().map(|_| self.fresh()).collect::<Vec<Type>>();
+                let tipe = Type::Tuple(
+                    Box::new(types[0].clone()),
+                    Box::new(types[1].clone()),
+                    types[2..].to_vec(),
+                );
+                self.unify_at(&pattern.region, expected, &tipe)?;
+                for (pattern, tipe) in patterns.iter().zip(types.iter()) {
+                    self.pattern(bindings, pattern, tipe)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // a record only needs to have the field being used, so anything else it has is left to a
+    // row variable.
+    fn field(&mut self, region: &Region, record_type: &Type, field: &Name) -> Result<Type, Error> {
+        if let Type::Record(fields, None) = self.apply(record_type) {
+            if !fields.contains_key(field) {
+                return Err(Error::MissingField {
+                    region: region.clone(),
+                    tipe: Type::Record(fields, None),
+                    field: field.clone(),
+                });
+            }
+        }
+
+        let field_type = self.fresh();
+        let rest = self.fresh_name();
+        self.unify_at(
+            region,
+            &Type::Record(
+                HashMap::from([(field.clone(), field_type.clone())]),
+                Some(rest),
+            ),
+            record_type,
+        )?;
+        Ok(field_type)
+    }
+
+    fn constructor(&self, constructor: &Qualified<Constructor>) -> Scheme {
+        let home = match constructor {
+            Qualified::Foreign { module, .. } => module,
+            Qualified::Local(_) | Qualified::Kernel(_) => &self.module,
+        };
+        let annotation = &constructor.get().annotation;
+        let mut quantified = annotation.quantified.iter().cloned().collect::<Vec<_>>();
+        quantified.sort();
+        Scheme(
+            quantified,
+            self.expand(&unify::qualify(&annotation.tipe, home)),
+        )
+    }
+
+    fn operator(&mut self, op: &Operator) -> (Type, Type, Type) {
+        match op {
+            Operator::Or | Operator::And => (bool(), bool(), bool()),
+            Operator::Eq
+            | Operator::Neq
+            | Operator::LT
+            | Operator::LTE
+            | Operator::GT
+            | Operator::GTE => {
+                let operand = self.fresh();
+                (operand.clone(), operand, bool())
+            }
+            // `++` is JavaScript's `+`, which only concatenates strings.
+            Operator::Concat => (string(), string(), string()),
+            Operator::Plus
+            | Operator::Minus
+            | Operator::Times
+            | Operator::Divide
+            | Operator::Mod
+            | Operator::Power => {
+                let operand = self.fresh_number();
+                (operand.clone(), operand.clone(), operand)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::ast::{canonical::Annotation, Located};
+
+    fn located(inner: Expr_) -> Expr {
+        Located {
+            region: Region::ZERO,
+            inner,
+        }
+    }
+
+    // `Box`, which wraps a single `Int`.
+    fn boxed() -> Expr {
+        let tipe = Type::Lambda(
+            Box::new(builtin("Num", "Int")),
+            Box::new(Type::Identifier(Qualified::Local("Box".to_owned()))),
+        );
+        located(Expr_::Constructor(Qualified::Local(Constructor {
+            tag: 0,
+            arity: 1,
+            annotation: Annotation {
+                quantified: HashSet::new(),
+                tipe,
+            },
+        })))
+    }
+
+    fn ap(function: Expr, arg: Expr) -> Expr {
+        located(Expr_::Ap {
+            function: Box::new(function),
+            arg: Box::new(arg),
+        })
+    }
+
+    fn infer(expr: &Expr) -> Result<Type, Error> {
+        let modules = HashMap::new();
+        let interfaces = HashMap::new();
+        let mut checker = Checker::new(ModuleName(vec!["Main".to_owned()]), &modules, &interfaces);
+        checker.infer(&Context::new(), expr)
+    }
+
+    #[test]
+    fn constructors_can_be_partially_applied() {
+        assert!(infer(&boxed()).is_ok());
+        assert!(infer(&ap(boxed(), located(Expr_::Int(1.0)))).is_ok());
+    }
+
+    #[test]
+    fn constructors_given_too_many_arguments_are_reported() {
+        let expr = ap(
+            ap(boxed(), located(Expr_::Int(1.0))),
+            located(Expr_::Int(2.0)),
+        );
+        assert!(matches!(
+            infer(&expr),
+            Err(Error::ConstructorArity {
+                expected: 1,
+                actual: 2,
+                pattern: false,
+                ..
+            })
+        ));
+    }
+}
@@ -1,17 +1,291 @@
-use std::collections::HashMap;
+mod infer;
+mod unify;
+
+use std::collections::{HashMap, HashSet};
 
 use crate::{
     ast::{
-        canonical::{self, Type},
-        ModuleName, Name,
+        canonical::{self, Annotation, Definition, Type},
+        ModuleName, Name, Qualified,
     },
+    interface::Interface,
     report::error::tipe::Error,
 };
 
+#[derive(Debug, Clone)]
 pub struct Scheme(Vec<Name>, Type);
 
 pub type Context = HashMap<Name, Scheme>;
 
-pub fn type_check(modules: &HashMap<ModuleName, canonical::Module>) -> Result<(), Error> {
+// modules with a cached interface have already been checked, so the annotations it records are
+// all that their dependents need. What comes back is the type of every top level definition,
+// including the ones which were inferred rather than annotated.
+pub fn type_check(
+    modules: &HashMap<ModuleName, canonical::Module>,
+    cached: &HashMap<ModuleName, Interface>,
+) -> Result<HashMap<ModuleName, HashMap<Name, Annotation>>, (ModuleName, Error)> {
+    let mut interfaces: HashMap<ModuleName, Context> = HashMap::new();
+    for module_name in dependency_order(modules) {
+        let mut checker = Checker::new(module_name.clone(), modules, &interfaces);
+        let interface = match cached.get(&module_name) {
+            Some(interface) => interface
+                .values
+                .iter()
+                .map(|(name, annotation)| (name.clone(), checker.annotation(annotation)))
+                .collect(),
+            None => checker
+                .module(&modules[&module_name])
+                .map_err(|error| (module_name.clone(), error))?,
+        };
+        interfaces.insert(module_name, interface);
+    }
+    Ok(interfaces
+        .into_iter()
+        .map(|(module_name, context)| {
+            let types = context
+                .into_iter()
+                .map(|(name, Scheme(quantified, tipe))| {
+                    let annotation = Annotation {
+                        quantified: quantified.into_iter().collect(),
+                        tipe,
+                    };
+                    (name, annotation)
+                })
+                .collect();
+            (module_name, types)
+        })
+        .collect())
+}
+
+// canonicalization already guarantees that there are no import cycles, so a depth first walk
+// over the imports is enough to check every module after the modules it depends on.
+fn dependency_order(modules: &HashMap<ModuleName, canonical::Module>) -> Vec<ModuleName> {
+    fn visit(
+        name: &ModuleName,
+        modules: &HashMap<ModuleName, canonical::Module>,
+        visited: &mut HashSet<ModuleName>,
+        order: &mut Vec<ModuleName>,
+    ) {
+        if !visited.insert(name.clone()) {
+            return;
+        }
+        if let Some(module) = modules.get(name) {
+            for import in module.imports.iter() {
+                visit(import, modules, visited, order);
+            }
+            order.push(name.clone());
+        }
+    }
+
+    let mut names = modules.keys().cloned().collect::<Vec<ModuleName>>();
+    names.sort_by_key(|name| name.to_string());
+
+    let mut visited = HashSet::new();
+    let mut order = vec![];
+    for name in names.iter() {
+        visit(name, modules, &mut visited, &mut order);
+    }
+    order
+}
+
+pub struct Checker<'a> {
+    module: ModuleName,
+    modules: &'a HashMap<ModuleName, canonical::Module>,
+    interfaces: &'a HashMap<ModuleName, Context>,
+    substitution: HashMap<Name, Type>,
+    next_variable: usize,
+}
+
+impl<'a> Checker<'a> {
+    fn new(
+        module: ModuleName,
+        modules: &'a HashMap<ModuleName, canonical::Module>,
+        interfaces: &'a HashMap<ModuleName, Context>,
+    ) -> Self {
+        Self {
+            module,
+            modules,
+            interfaces,
+            substitution: HashMap::new(),
+            next_variable: 0,
+        }
+    }
+
+    fn module(&mut self, module: &canonical::Module) -> Result<Context, Error> {
+        let mut unions = module.unions.iter().collect::<Vec<_>>();
+        unions.sort_by_key(|(name, _)| *name);
+        for (name, union) in unions {
+            union_variables(name, union)?;
+        }
+
+        let mut context = Context::new();
+        for definitions in module.definitions.iter() {
+            match definitions {
+                canonical::Definitions::Recursive(definitions) => {
+                    self.definitions(&mut context, definitions)?
+                }
+                canonical::Definitions::NonRecursive(definition) => {
+                    self.definitions(&mut context, std::slice::from_ref(definition))?
+                }
+            }
+        }
+        Ok(context)
+    }
+
+    fn definitions(
+        &mut self,
+        context: &mut Context,
+        definitions: &[Definition],
+    ) -> Result<(), Error> {
+        // recursive references to an annotated definition can use its annotation directly, but an
+        // unannotated one only gets a placeholder until the whole group has been inferred.
+        let mut placeholders = HashMap::new();
+        for definition in definitions.iter() {
+            let scheme = match &definition.annotation {
+                Some(annotation) => self.annotation(annotation),
+                None => {
+                    let placeholder = self.fresh();
+                    placeholders.insert(definition.name.clone(), placeholder.clone());
+                    Scheme(vec![], placeholder)
+                }
+            };
+            context.insert(definition.name.clone(), scheme);
+        }
+
+        for definition in definitions.iter() {
+            match &definition.annotation {
+                Some(annotation) => self.definition(context, definition, annotation)?,
+                None => self.check(context, &definition.expr, &placeholders[&definition.name])?,
+            }
+        }
+
+        // the placeholders are taken out first so that the group's definitions don't stop each
+        // other's type variables from being generalized.
+        for name in placeholders.keys() {
+            context.remove(name);
+        }
+        let schemes = placeholders
+            .iter()
+            .map(|(name, placeholder)| (name.clone(), self.generalize(context, placeholder)))
+            .collect::<Vec<(Name, Scheme)>>();
+        context.extend(schemes);
+        Ok(())
+    }
+
+    fn definition(
+        &mut self,
+        context: &Context,
+        definition: &Definition,
+        annotation: &Annotation,
+    ) -> Result<(), Error> {
+        let Scheme(quantified, tipe) = self.annotation(annotation);
+        let rigid = quantified
+            .iter()
+            .map(|var| (var.clone(), self.fresh_like(var)))
+            .collect::<HashMap<Name, Type>>();
+        let expected = unify::substitute(&tipe, &rigid);
+
+        self.check(context, &definition.expr, &expected)?;
+
+        // the annotation promises that each of its type variables can be anything, so none of
+        // them may have been pinned down to a concrete type, merged with another variable or
+        // restricted to numbers.
+        let mut seen = HashSet::new();
+        for var in quantified.iter() {
+            match self.apply(&rigid[var]) {
+                Type::Variable(fresh)
+                    if (unify::is_number(var) || !unify::is_number(&fresh))
+                        && seen.insert(fresh.clone()) => {}
+                _ => {
+                    return Err(Error::AnnotationTooGeneral {
+                        region: definition.expr.region.clone(),
+                        name: definition.name.clone(),
+                        annotation: tipe,
+                        inferred: self.apply(&expected),
+                    })
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // the variables are only quantified once aliases have been expanded, since the type an alias
+    // stands for is what's actually checked.
+    fn annotation(&self, annotation: &Annotation) -> Scheme {
+        let tipe = self.expand(&unify::qualify(&annotation.tipe, &self.module));
+        let mut quantified = tipe.free_variables().into_iter().collect::<Vec<Name>>();
+        quantified.sort();
+        Scheme(quantified, tipe)
+    }
+
+    // replaces every alias in a qualified type with the type it stands for, so that unification
+    // only ever sees unions, extern types and structural types. Canonicalization has already made
+    // sure each alias gets one argument per variable and that none of them refer to themselves.
+    fn expand(&self, tipe: &Type) -> Type {
+        let mut cons = tipe;
+        let mut args = vec![];
+        while let Type::Application(f, arg) = cons {
+            args.push(arg.as_ref());
+            cons = f;
+        }
+        if let Type::Identifier(Qualified::Foreign { module, member }) = cons {
+            if let Some(alias) = self
+                .modules
+                .get(module)
+                .and_then(|home| home.aliases.get(member))
+            {
+                let arguments = alias
+                    .variables
+                    .iter()
+                    .cloned()
+                    .zip(args.into_iter().rev().map(|arg| self.expand(arg)))
+                    .collect::<HashMap<Name, Type>>();
+                let other = unify::qualify(&alias.other, module);
+                return self.expand(&unify::substitute(&other, &arguments));
+            }
+        }
+
+        match tipe {
+            Type::Variable(_) | Type::Identifier(_) | Type::Unit => tipe.clone(),
+            Type::Application(cons, arg) => {
+                Type::Application(Box::new(self.expand(cons)), Box::new(self.expand(arg)))
+            }
+            Type::Lambda(arg, ret) => {
+                Type::Lambda(Box::new(self.expand(arg)), Box::new(self.expand(ret)))
+            }
+            Type::Record(fields, extension) => Type::Record(
+                fields
+                    .iter()
+                    .map(|(field, tipe)| (field.clone(), self.expand(tipe)))
+                    .collect(),
+                extension.clone(),
+            ),
+            Type::Tuple(first, second, rest) => Type::Tuple(
+                Box::new(self.expand(first)),
+                Box::new(self.expand(second)),
+                rest.iter().map(|tipe| self.expand(tipe)).collect(),
+            ),
+        }
+    }
+}
+
+fn union_variables(name: &Name, union: &canonical::Union) -> Result<(), Error> {
+    for variant in union.variants.iter() {
+        for arg in variant.args.iter() {
+            let mut free = arg
+                .inner
+                .free_variables()
+                .into_iter()
+                .collect::<Vec<Name>>();
+            free.sort();
+            if let Some(variable) = free.into_iter().find(|var| !union.variables.contains(var)) {
+                return Err(Error::UnboundTypeVariable {
+                    region: arg.region.clone(),
+                    tipe: name.clone(),
+                    variable,
+                });
+            }
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,456 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    ast::{canonical::Type, ModuleName, Name, Qualified, Region},
+    report::error::tipe::Error,
+};
+
+use super::{Checker, Context, Scheme};
+
+pub enum Failure {
+    Mismatch,
+    Infinite(Name, Type),
+}
+
+impl<'a> Checker<'a> {
+    pub fn fresh(&mut self) -> Type {
+        Type::Variable(self.fresh_name())
+    }
+
+    pub fn fresh_name(&mut self) -> Name {
+        self.next_variable += 1;
+        format!("?{}", self.next_variable)
+    }
+
+    pub fn fresh_number(&mut self) -> Type {
+        self.next_variable += 1;
+        Type::Variable(format!("?number{}", self.next_variable))
+    }
+
+    // a fresh variable standing in for `var`, which keeps any constraint `var` has.
+    pub fn fresh_like(&mut self, var: &Name) -> Type {
+        if is_number(var) {
+            self.fresh_number()
+        } else {
+            self.fresh()
+        }
+    }
+
+    // SUBSTITUTION
+
+    pub fn apply(&self, tipe: &Type) -> Type {
+        match tipe {
+            Type::Variable(var) => match self.substitution.get(var) {
+                Some(tipe) => self.apply(tipe),
+                None => tipe.clone(),
+            },
+            Type::Identifier(_) => tipe.clone(),
+            Type::Unit => Type::Unit,
+            Type::Application(cons, arg) => {
+                Type::Application(Box::new(self.apply(cons)), Box::new(self.apply(arg)))
+            }
+            Type::Lambda(arg, ret) => {
+                Type::Lambda(Box::new(self.apply(arg)), Box::new(self.apply(ret)))
+            }
+            Type::Record(fields, extension) => {
+                let fields = fields
+                    .iter()
+                    .map(|(field, tipe)| (field.clone(), self.apply(tipe)))
+                    .collect();
+                match extension {
+                    Some(var) => extend(fields, self.apply(&Type::Variable(var.clone()))),
+                    None => Type::Record(fields, None),
+                }
+            }
+            Type::Tuple(first, second, rest) => Type::Tuple(
+                Box::new(self.apply(first)),
+                Box::new(self.apply(second)),
+                rest.iter().map(|tipe| self.apply(tipe)).collect(),
+            ),
+        }
+    }
+
+    // SCHEMES
+
+    pub fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let Scheme(quantified, tipe) = scheme;
+        let fresh = quantified
+            .iter()
+            .map(|var| (var.clone(), self.fresh_like(var)))
+            .collect::<HashMap<Name, Type>>();
+        substitute(tipe, &fresh)
+    }
+
+    pub fn generalize(&self, context: &Context, tipe: &Type) -> Scheme {
+        let tipe = self.apply(tipe);
+        let in_context = context
+            .values()
+            .flat_map(|Scheme(quantified, tipe)| {
+                self.apply(tipe)
+                    .free_variables()
+                    .into_iter()
+                    .filter(|var| !quantified.contains(var))
+                    .collect::<Vec<Name>>()
+            })
+            .collect::<HashSet<Name>>();
+        let mut quantified = tipe
+            .free_variables()
+            .into_iter()
+            .filter(|var| !in_context.contains(var))
+            .collect::<Vec<Name>>();
+        quantified.sort();
+        Scheme(quantified, tipe)
+    }
+
+    // UNIFICATION
+
+    pub fn unify_at(
+        &mut self,
+        region: &Region,
+        expected: &Type,
+        actual: &Type,
+    ) -> Result<(), Error> {
+        match self.unify(expected, actual) {
+            Ok(()) => Ok(()),
+            Err(Failure::Mismatch) => Err(Error::Mismatch {
+                region: region.clone(),
+                expected: self.apply(expected),
+                actual: self.apply(actual),
+            }),
+            Err(Failure::Infinite(variable, tipe)) => Err(Error::InfiniteType {
+                region: region.clone(),
+                variable,
+                tipe: self.apply(&tipe),
+            }),
+        }
+    }
+
+    fn unify(&mut self, lhs: &Type, rhs: &Type) -> Result<(), Failure> {
+        match (self.apply(lhs), self.apply(rhs)) {
+            (Type::Variable(a), Type::Variable(b)) if a == b => Ok(()),
+            (Type::Variable(var), tipe) | (tipe, Type::Variable(var)) => self.bind(var, tipe),
+            (Type::Identifier(a), Type::Identifier(b)) if a == b => Ok(()),
+            (Type::Unit, Type::Unit) => Ok(()),
+            (Type::Application(f, x), Type::Application(g, y)) => {
+                self.unify(&f, &g)?;
+                self.unify(&x, &y)
+            }
+            (Type::Lambda(a, r), Type::Lambda(b, s)) => {
+                self.unify(&a, &b)?;
+                self.unify(&r, &s)
+            }
+            (Type::Record(a, a_extension), Type::Record(b, b_extension)) => {
+                for (field, tipe) in a.iter() {
+                    if let Some(other) = b.get(field) {
+                        self.unify(tipe, other)?;
+                    }
+                }
+
+                // whatever fields only one side has must be covered by the other side's row
+                // variable, so each row variable is solved with the fields it's missing.
+                let only_a = missing_from(&a, &b);
+                let only_b = missing_from(&b, &a);
+                match (a_extension, b_extension) {
+                    (None, None) if only_a.is_empty() && only_b.is_empty() => Ok(()),
+                    (Some(a_var), None) if only_a.is_empty() => {
+                        self.bind(a_var, Type::Record(only_b, None))
+                    }
+                    (None, Some(b_var)) if only_b.is_empty() => {
+                        self.bind(b_var, Type::Record(only_a, None))
+                    }
+                    (Some(a_var), Some(b_var)) if a_var == b_var => {
+                        if only_a.is_empty() && only_b.is_empty() {
+                            Ok(())
+                        } else {
+                            Err(Failure::Mismatch)
+                        }
+                    }
+                    (Some(a_var), Some(b_var)) => {
+                        let rest = self.fresh();
+                        self.bind(a_var, extend(only_b, rest.clone()))?;
+                        self.bind(b_var, extend(only_a, rest))
+                    }
+                    _ => Err(Failure::Mismatch),
+                }
+            }
+            (Type::Tuple(a1, a2, a_rest), Type::Tuple(b1, b2, b_rest)) => {
+                if a_rest.len() != b_rest.len() {
+                    return Err(Failure::Mismatch);
+                }
+                self.unify(&a1, &b1)?;
+                self.unify(&a2, &b2)?;
+                for (a, b) in a_rest.iter().zip(b_rest.iter()) {
+                    self.unify(a, b)?;
+                }
+                Ok(())
+            }
+            _ => Err(Failure::Mismatch),
+        }
+    }
+
+    fn bind(&mut self, var: Name, tipe: Type) -> Result<(), Failure> {
+        let tipe = self.apply(&tipe);
+        if tipe == Type::Variable(var.clone()) {
+            Ok(())
+        } else if is_number(&var) {
+            match tipe {
+                // the unconstrained variable takes on the constraint instead.
+                Type::Variable(other) if !is_number(&other) => {
+                    self.substitution.insert(other, Type::Variable(var));
+                    Ok(())
+                }
+                Type::Variable(_) => {
+                    self.substitution.insert(var, tipe);
+                    Ok(())
+                }
+                Type::Identifier(Qualified::Foreign { module, member })
+                    if module.0 == ["Num"] && (member == "Int" || member == "Float") =>
+                {
+                    self.substitution
+                        .insert(var, Type::Identifier(Qualified::Foreign { module, member }));
+                    Ok(())
+                }
+                _ => Err(Failure::Mismatch),
+            }
+        } else if tipe.free_variables().contains(&var) {
+            Err(Failure::Infinite(var, tipe))
+        } else {
+            self.substitution.insert(var, tipe);
+            Ok(())
+        }
+    }
+}
+
+// type variables which can only stand for `Num.Int` or `Num.Float`, like the operands of the
+// arithmetic operators. Annotations ask for one by naming a variable `number`.
+pub fn is_number(var: &str) -> bool {
+    var.trim_start_matches('?').starts_with("number")
+}
+
+fn missing_from(fields: &HashMap<Name, Type>, other: &HashMap<Name, Type>) -> HashMap<Name, Type> {
+    fields
+        .iter()
+        .filter(|(field, _)| !other.contains_key(*field))
+        .map(|(field, tipe)| (field.clone(), tipe.clone()))
+        .collect()
+}
+
+// adds fields to a record given what its row variable stands for, which is either another record
+// or a variable if the rest of the record is still unknown.
+pub fn extend(fields: HashMap<Name, Type>, rest: Type) -> Type {
+    match rest {
+        Type::Variable(var) if fields.is_empty() => Type::Variable(var),
+        Type::Variable(var) => Type::Record(fields, Some(var)),
+        Type::Record(mut more, extension) => {
+            more.extend(fields);
+            Type::Record(more, extension)
+        }
+        // a row variable can only be solved with something other than a record if it was also
+        // used as a regular type variable, which kind checking rules out.
+        _ => Type::Record(fields, None),
+    }
+}
+
+pub fn substitute(tipe: &Type, substitution: &HashMap<Name, Type>) -> Type {
+    match tipe {
+        Type::Variable(var) => substitution.get(var).cloned().unwrap_or(tipe.clone()),
+        Type::Identifier(_) => tipe.clone(),
+        Type::Unit => Type::Unit,
+        Type::Application(cons, arg) => Type::Application(
+            Box::new(substitute(cons, substitution)),
+            Box::new(substitute(arg, substitution)),
+        ),
+        Type::Lambda(arg, ret) => Type::Lambda(
+            Box::new(substitute(arg, substitution)),
+            Box::new(substitute(ret, substitution)),
+        ),
+        Type::Record(fields, extension) => {
+            let fields = fields
+                .iter()
+                .map(|(field, tipe)| (field.clone(), substitute(tipe, substitution)))
+                .collect();
+            match extension {
+                Some(var) => extend(
+                    fields,
+                    substitution
+                        .get(var)
+                        .cloned()
+                        .unwrap_or(Type::Variable(var.clone())),
+                ),
+                None => Type::Record(fields, None),
+            }
+        }
+        Type::Tuple(first, second, rest) => Type::Tuple(
+            Box::new(substitute(first, substitution)),
+            Box::new(substitute(second, substitution)),
+            rest.iter()
+                .map(|tipe| substitute(tipe, substitution))
+                .collect(),
+        ),
+    }
+}
+
+// types are canonicalized relative to the module they're defined in, so before comparing types
+// from different modules every local type name needs to be qualified with its home module.
+pub fn qualify(tipe: &Type, home: &ModuleName) -> Type {
+    match tipe {
+        Type::Identifier(Qualified::Local(name)) => Type::Identifier(Qualified::Foreign {
+            module: home.clone(),
+            member: name.clone(),
+        }),
+        Type::Identifier(_) | Type::Variable(_) | Type::Unit => tipe.clone(),
+        Type::Application(cons, arg) => {
+            Type::Application(Box::new(qualify(cons, home)), Box::new(qualify(arg, home)))
+        }
+        Type::Lambda(arg, ret) => {
+            Type::Lambda(Box::new(qualify(arg, home)), Box::new(qualify(ret, home)))
+        }
+        Type::Record(fields, extension) => Type::Record(
+            fields
+                .iter()
+                .map(|(field, tipe)| (field.clone(), qualify(tipe, home)))
+                .collect(),
+            extension.clone(),
+        ),
+        Type::Tuple(first, second, rest) => Type::Tuple(
+            Box::new(qualify(first, home)),
+            Box::new(qualify(second, home)),
+            rest.iter().map(|tipe| qualify(tipe, home)).collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int() -> Type {
+        Type::Identifier(Qualified::Foreign {
+            module: ModuleName(vec!["Num".to_owned()]),
+            member: "Int".to_owned(),
+        })
+    }
+
+    fn string() -> Type {
+        Type::Identifier(Qualified::Foreign {
+            module: ModuleName(vec!["String".to_owned()]),
+            member: "String".to_owned(),
+        })
+    }
+
+    fn lambda(arg: Type, ret: Type) -> Type {
+        Type::Lambda(Box::new(arg), Box::new(ret))
+    }
+
+    fn with_checker(test: impl FnOnce(&mut Checker)) {
+        let modules = HashMap::new();
+        let interfaces = HashMap::new();
+        let mut checker = Checker::new(ModuleName(vec!["Main".to_owned()]), &modules, &interfaces);
+        test(&mut checker);
+    }
+
+    #[test]
+    fn variables_are_solved_through_functions() {
+        with_checker(|checker| {
+            let a = checker.fresh();
+            let b = checker.fresh();
+            assert!(checker
+                .unify(&lambda(a.clone(), b.clone()), &lambda(int(), string()))
+                .is_ok());
+            assert_eq!(checker.apply(&a), int());
+            assert_eq!(checker.apply(&b), string());
+        });
+    }
+
+    #[test]
+    fn different_types_do_not_unify() {
+        with_checker(|checker| {
+            let a = checker.fresh();
+            assert!(checker.unify(&a, &int()).is_ok());
+            assert!(matches!(
+                checker.unify(&a, &string()),
+                Err(Failure::Mismatch)
+            ));
+        });
+    }
+
+    #[test]
+    fn a_variable_cannot_contain_itself() {
+        with_checker(|checker| {
+            let a = checker.fresh();
+            let list = Type::Application(Box::new(string()), Box::new(a.clone()));
+            assert!(matches!(
+                checker.unify(&a, &list),
+                Err(Failure::Infinite(_, _))
+            ));
+        });
+    }
+
+    #[test]
+    fn open_records_take_the_fields_they_are_missing() {
+        with_checker(|checker| {
+            let open = Type::Record(
+                HashMap::from([("x".to_owned(), int())]),
+                Some("r".to_owned()),
+            );
+            let closed = Type::Record(
+                HashMap::from([("x".to_owned(), int()), ("y".to_owned(), string())]),
+                None,
+            );
+            assert!(checker.unify(&open, &closed).is_ok());
+            assert_eq!(checker.apply(&open), closed);
+        });
+    }
+
+    #[test]
+    fn numbers_are_only_ints_or_floats() {
+        with_checker(|checker| {
+            let number = checker.fresh_number();
+            assert!(checker.unify(&number, &int()).is_ok());
+            assert_eq!(checker.apply(&number), int());
+
+            let number = checker.fresh_number();
+            assert!(checker.unify(&number, &string()).is_err());
+        });
+    }
+
+    #[test]
+    fn unconstrained_variables_take_on_the_constraint() {
+        with_checker(|checker| {
+            let a = checker.fresh();
+            let number = checker.fresh_number();
+            assert!(checker.unify(&a, &number).is_ok());
+            assert!(checker.unify(&a, &string()).is_err());
+            assert!(checker.unify(&a, &int()).is_ok());
+            assert_eq!(checker.apply(&number), int());
+        });
+    }
+
+    #[test]
+    fn generalization_skips_variables_in_the_context() {
+        with_checker(|checker| {
+            let a = checker.fresh();
+            let b = checker.fresh();
+            let context = Context::from([("x".to_owned(), Scheme(vec![], a.clone()))]);
+            let Scheme(quantified, _) = checker.generalize(&context, &lambda(a, b.clone()));
+            let Type::Variable(b) = b else { unreachable!() };
+            assert_eq!(quantified, vec![b]);
+        });
+    }
+
+    #[test]
+    fn instantiation_gives_fresh_variables_with_the_same_constraints() {
+        with_checker(|checker| {
+            let a = checker.fresh();
+            let number = checker.fresh_number();
+            let scheme = checker.generalize(&Context::new(), &lambda(a.clone(), number.clone()));
+            let Type::Lambda(arg, ret) = checker.instantiate(&scheme) else {
+                panic!("instantiating a function gives a function");
+            };
+            assert_ne!(*arg, a);
+            assert_ne!(*ret, number);
+            assert!(matches!(*ret, Type::Variable(var) if is_number(&var)));
+            assert!(matches!(*arg, Type::Variable(var) if !is_number(&var)));
+        });
+    }
+}
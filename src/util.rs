@@ -35,3 +35,44 @@ where
         full
     })
 }
+
+// the candidates which are close enough to `name` that they might be what was meant, closest
+// first.
+pub fn suggestions<'a, I>(name: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let limit = (name.chars().count() + 2) / 3;
+    let mut close = candidates
+        .into_iter()
+        .filter(|candidate| *candidate != name)
+        .map(|candidate| {
+            let distance = edit_distance(&name.to_lowercase(), &candidate.to_lowercase());
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= limit)
+        .collect::<Vec<(usize, &str)>>();
+    close.sort();
+    close.dedup();
+    close
+        .into_iter()
+        .take(4)
+        .map(|(_, candidate)| candidate.to_owned())
+        .collect()
+}
+
+// the number of single character insertions, deletions and substitutions it takes to turn one
+// string into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<char>>();
+    let mut previous = (0..=b.len()).collect::<Vec<usize>>();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}